@@ -34,3 +34,38 @@ fn impl_validator_macro(ast: &syn::DeriveInput) -> TokenStream {
         _ => panic!("Can only derive for Enum"),
     }
 }
+
+#[proc_macro_derive(Merge)]
+pub fn merge_macro_derive(input: TokenStream) -> TokenStream {
+    let ast = syn::parse(input).unwrap();
+    impl_merge_macro(&ast)
+}
+
+fn impl_merge_macro(ast: &syn::DeriveInput) -> TokenStream {
+    let name = &ast.ident;
+    let data: &Data = &ast.data;
+    let generics = &ast.generics;
+    match data {
+        Data::Enum(data_enum) => {
+            let match_arms = data_enum.variants.iter().map(|variant| {
+                let variant_ident = &variant.ident;
+                quote! {
+                    (#name::#variant_ident(x), #name::#variant_ident(y)) => x.merge(y),
+                }
+            });
+
+            quote! {
+            impl #generics Merge for #name #generics {
+                fn merge(&mut self, other: Self) -> bool {
+                    #[allow(unreachable_patterns)]
+                    match (self, other) {
+                        #(#match_arms)*
+                        _ => false,
+                    }
+                }
+            }}
+            .into()
+        }
+        _ => panic!("Can only derive for Enum"),
+    }
+}
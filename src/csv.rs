@@ -1,21 +1,95 @@
-use std::error::Error;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::File,
+    io::{self, Cursor, Read},
+};
 
+pub use csv::{Position, Terminator, Trim};
 use csv::{Reader, ReaderBuilder};
-pub use csv::{Terminator, Trim};
+pub(crate) use progress::Tracker;
+#[cfg(feature = "json")]
+pub(crate) use sampling::{keep, Sample, Sampler};
+#[cfg(feature = "json")]
+pub(crate) use session::{new_field, reject};
 
 use crate::{
-    field::Fields, DataType, Date, DateTime, Empty, Field, Float, Integer, Text, Time, Validator,
+    field::Fields, DataType, Date, DateTime, Empty, Float, Integer, Text, Time, Tolerance,
+    Validator,
+};
+
+mod compression;
+mod encoding;
+mod files;
+mod frame;
+mod header;
+mod parallel;
+mod progress;
+mod sampling;
+mod select;
+mod session;
+mod sniff;
+mod validate;
+pub use compression::Compression;
+pub use encoding::{Encoding, ENCODING_SAMPLE_LEN};
+pub use files::{infer_csv_files, FileFields, FilesInference, HeaderMismatch, TABLE_EXTENSIONS};
+pub use frame::PREAMBLE_SAMPLE_LEN;
+pub use header::{
+    detect_headers, normalize_header, HeaderCase, HeaderDetection, HeaderEvidence, HeaderVote,
+    HEADER_SAMPLE_LEN,
 };
+pub use parallel::infer_csv_parallel;
+pub use progress::{Progress, ProgressCallback, PROGRESS_INTERVAL};
+pub use sampling::SamplingStrategy;
+pub use select::{ColumnSelector, Override};
+use session::infer_records;
+pub use session::InferSession;
+pub use sniff::{sniff, Dialect, SAMPLE_LEN};
+pub use validate::{validate_csv, Expected, ValidateOptions, ValidationReport, Violation};
 
 pub enum CsvInput<'a> {
     Path(&'a str),
     Value(&'a str),
+    /// Raw bytes, which need not be valid UTF-8 as a whole.
+    Bytes(&'a [u8]),
+    /// Any reader, such as a socket or a decompression stream. It is read
+    /// incrementally rather than buffered up front.
+    Reader(Box<dyn Read + 'a>),
+    Stdin,
 }
 
 pub struct InferOptions {
     pub data_types: Vec<DataType>,
     pub null_validator: DataType,
+    /// Values taken as null along with those matching `null_validator`, such
+    /// as [`COMMON_NULL_TOKENS`].
+    pub null_tokens: Vec<String>,
     pub has_headers: bool,
+    /// Lines of the input to skip before anything else.
+    pub skip_lines: u64,
+    /// Index of the header row among the records after `skip_lines`. Any
+    /// records before it are skipped, whether or not there is a header.
+    pub header_row: usize,
+    /// Skips records before the table whose number of fields differs from
+    /// most of the first [`PREAMBLE_SAMPLE_LEN`] records, such as the title
+    /// of a report.
+    pub detect_preamble: bool,
+    /// Skips records at the end of the input whose number of fields differs
+    /// from the table, such as a line of totals.
+    pub detect_footer: bool,
+    /// Lines starting with this byte are ignored.
+    pub comment: Option<u8>,
+    /// Decides whether there is a header row from the data itself, overriding
+    /// `has_headers`. See [`detect_headers`].
+    pub detect_headers: bool,
+    /// How headers are rewritten into field names, after any trimming. The
+    /// header as it was is kept in [`Field::header`](crate::Field::header).
+    pub header_case: HeaderCase,
+    /// Gives repeated names a suffix, so that the second `id` becomes `id_2`.
+    pub dedupe_headers: bool,
+    /// Names columns with a null or missing header `column_1`, `column_2` and
+    /// so on, by their position counting from 1.
+    pub name_missing_headers: bool,
     pub flexible: bool,
     pub delimiter: u8,
     pub escape: Option<u8>,
@@ -23,6 +97,33 @@ pub struct InferOptions {
     pub quoting: bool,
     pub trim: Trim,
     pub terminator: Terminator,
+    /// Compression of the input, detected from its first few bytes when
+    /// `None`.
+    pub compression: Option<Compression>,
+    /// Encoding of the input, after any decompression, detected from its
+    /// first few bytes when `None`. Input is transcoded to UTF-8 as it is
+    /// read, except when pushed to an [`InferSession`].
+    pub encoding: Option<Encoding>,
+    pub sampling: SamplingStrategy,
+    /// How many malformed records, such as ones with the wrong number of
+    /// fields or invalid UTF-8, are skipped and listed in
+    /// [`Report::rejected`](crate::Report::rejected) before giving up.
+    /// Inference fails on the first one when `None`.
+    pub max_errors: Option<usize>,
+    /// How many values each type may reject while still being inferred.
+    pub tolerance: Tolerance,
+    /// Tolerances for particular types, by [name](DataType::name), overriding
+    /// `tolerance`.
+    pub tolerances: HashMap<String, Tolerance>,
+    /// Called every so often with how far inference has got, and able to
+    /// stop it early.
+    pub progress: Option<ProgressCallback>,
+    /// Only the columns picked out by any of these selectors are inferred,
+    /// and the rest skipped. Every column is inferred when `None`.
+    pub columns: Option<Vec<ColumnSelector>>,
+    /// Changes to `data_types` for particular columns, applied in order to
+    /// every column each selector matches.
+    pub overrides: Vec<(ColumnSelector, Override)>,
 }
 
 impl Default for InferOptions {
@@ -40,7 +141,17 @@ impl Default for InferOptions {
                 DataType::DateTime(DateTime::default()),
             ],
             null_validator: DataType::Empty(Empty),
+            null_tokens: vec![],
             has_headers: false,
+            skip_lines: 0,
+            header_row: 0,
+            detect_preamble: false,
+            detect_footer: false,
+            comment: None,
+            detect_headers: false,
+            header_case: HeaderCase::Keep,
+            dedupe_headers: false,
+            name_missing_headers: false,
             flexible: false,
             delimiter: b',',
             escape: None,
@@ -48,85 +159,161 @@ impl Default for InferOptions {
             quoting: true,
             trim: Trim::None,
             terminator: Terminator::CRLF,
+            compression: None,
+            encoding: None,
+            sampling: SamplingStrategy::All,
+            max_errors: None,
+            tolerance: Tolerance::default(),
+            tolerances: HashMap::new(),
+            progress: None,
+            columns: None,
+            overrides: vec![],
         }
     }
 }
 
+impl InferOptions {
+    /// The tolerance for `data_type`.
+    pub fn tolerance_for(&self, data_type: &DataType) -> Tolerance {
+        self.tolerances
+            .get(data_type.name())
+            .copied()
+            .unwrap_or(self.tolerance)
+    }
+}
+
+/// Sentinels commonly used for missing values, for
+/// [`InferOptions::null_tokens`].
+pub const COMMON_NULL_TOKENS: [&str; 9] = [
+    "NA", "N/A", "n/a", "null", "NULL", "None", "NaN", "-", "\\N",
+];
+
+/// Whether `value` is null, by `null_validator` or one of the null tokens.
+pub(crate) fn is_null(options: &InferOptions, null_validator: &mut DataType, value: &str) -> bool {
+    options.null_tokens.iter().any(|token| token == value) || null_validator.validate(value)
+}
+
+/// Opens `input` as a reader of plain UTF-8 CSV bytes, decompressing and
+/// transcoding it if need be. Gives the encoding the input was in.
+pub(crate) fn open<'a>(
+    input: CsvInput<'a>,
+    options: &InferOptions,
+) -> Result<(Box<dyn Read + 'a>, Encoding), Box<dyn Error>> {
+    let path = match input {
+        CsvInput::Path(path) => Some(path),
+        _ => None,
+    };
+    let reader: Box<dyn Read + 'a> = match input {
+        CsvInput::Path(path) => Box::new(File::open(path)?),
+        CsvInput::Value(value) => Box::new(value.as_bytes()),
+        CsvInput::Bytes(bytes) => Box::new(bytes),
+        CsvInput::Reader(reader) => reader,
+        CsvInput::Stdin => Box::new(io::stdin()),
+    };
+    let reader = compression::decompress(reader, path, options.compression)?;
+    encoding::transcode(reader, options.encoding)
+}
+
+fn reader_builder(options: &InferOptions) -> ReaderBuilder {
+    let mut reader_builder = ReaderBuilder::new();
+    // Headers, record lengths and trimming are handled by the `InferSession`
+    // so that records and raw byte chunks are treated alike.
+    reader_builder
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(options.delimiter)
+        .escape(options.escape)
+        .quote(options.quote)
+        .quoting(options.quoting)
+        .comment(options.comment)
+        .terminator(options.terminator);
+    reader_builder
+}
+
+type CsvReader<'a> = Reader<Box<dyn Read + 'a>>;
+
+fn csv_reader<'a>(
+    input: CsvInput<'a>,
+    options: &InferOptions,
+) -> Result<(CsvReader<'a>, Encoding), Box<dyn Error>> {
+    let (reader, encoding) = open(input, options)?;
+    Ok((reader_builder(options).from_reader(reader), encoding))
+}
+
 fn infer_csv_with_reader<R>(
-    options: &mut InferOptions,
+    options: &InferOptions,
     reader: &mut Reader<R>,
 ) -> Result<Fields, Box<dyn Error>>
 where
-    R: std::io::Read,
+    R: Read,
 {
-    let mut fields = Fields(
-        reader
-            .headers()?
-            .iter()
-            .map(|value| {
-                Field::new(
-                    if options.has_headers {
-                        if options.null_validator.validate(value) {
-                            None
-                        } else {
-                            Some(value.to_string())
-                        }
-                    } else {
-                        None
-                    },
-                    options.data_types.clone(),
-                )
-            })
-            .collect(),
-    );
-    for record in reader.records() {
-        let record = record?;
-
-        if options.flexible {
-            for _ in 0..record.len().saturating_sub(fields.len()) {
-                fields.push(Field::new_with_nullable(
-                    None,
-                    options.data_types.to_owned(),
-                    true,
-                ))
-            }
+    if is_plain(options) {
+        return infer_records(options, reader);
+    }
+    let mut session = InferSession::new(options);
+    let mut tracker = Tracker::new(options.progress.as_ref());
+    let mut incomplete = false;
+    let mut records = reader.records();
+    while let Some(record) = records.next() {
+        match record {
+            Ok(record) => session.push_record(&record)?,
+            Err(err) if err.is_io_error() => return Err(err.into()),
+            Err(err) => session.reject(err.position().cloned(), err.into())?,
         }
-
-        for (value, field) in record.iter().zip(fields.iter_mut()) {
-            if options.null_validator.validate(value) {
-                field.nullable = true
-            } else {
-                field.consider(value)
-            }
+        if session.done() {
+            break;
+        }
+        if tracker
+            .record(records.reader().position().byte())
+            .is_break()
+        {
+            incomplete = true;
+            break;
         }
     }
+    let mut fields = session.finish()?;
+    fields.report.incomplete = incomplete;
     Ok(fields)
 }
 
+/// Whether every record is simply considered in turn, so that inference need
+/// not go through an [`InferSession`].
+fn is_plain(options: &InferOptions) -> bool {
+    options.sampling == SamplingStrategy::All
+        && options.progress.is_none()
+        && !options.detect_headers
+        && options.skip_lines == 0
+        && options.header_row == 0
+        && !options.detect_preamble
+        && !options.detect_footer
+}
+
 pub fn infer_csv_with_options(
     input: CsvInput,
     options: &mut InferOptions,
 ) -> Result<Fields, Box<dyn Error>> {
-    let mut reader_builder = ReaderBuilder::new();
-    let reader_builder = reader_builder
-        .has_headers(options.has_headers)
-        .flexible(options.flexible)
-        .delimiter(options.delimiter)
-        .escape(options.escape)
-        .quote(options.quote)
-        .quoting(options.quoting)
-        .trim(options.trim)
-        .terminator(options.terminator);
+    let (mut reader, encoding) = csv_reader(input, options)?;
+    let mut fields = infer_csv_with_reader(options, &mut reader)?;
+    fields.report.encoding = Some(encoding);
+    Ok(fields)
+}
 
-    match input {
-        CsvInput::Path(path) => {
-            infer_csv_with_reader(options, &mut reader_builder.from_path(path)?)
-        }
-        CsvInput::Value(value) => {
-            let mut reader_builder = reader_builder.from_reader(value.as_bytes());
-            infer_csv_with_reader(options, &mut reader_builder)
-        }
-    }
+/// Like [`infer_csv_with_options`], but first sniffs the dialect from the start
+/// of `input` and overwrites the dialect fields of `options` with it.
+pub fn infer_csv_auto(
+    input: CsvInput,
+    options: &mut InferOptions,
+) -> Result<Fields, Box<dyn Error>> {
+    let (mut reader, encoding) = open(input, options)?;
+    let mut sample = Vec::new();
+    (&mut reader).take(SAMPLE_LEN).read_to_end(&mut sample)?;
+    sniff(&sample).apply(options);
+
+    let reader = Cursor::new(sample).chain(reader);
+    let mut fields =
+        infer_csv_with_reader(options, &mut reader_builder(options).from_reader(reader))?;
+    fields.report.encoding = Some(encoding);
+    Ok(fields)
 }
 
 pub fn infer_csv(input: CsvInput) -> Result<Fields, Box<dyn Error>> {
@@ -137,7 +324,10 @@ pub fn infer_csv(input: CsvInput) -> Result<Fields, Box<dyn Error>> {
 mod test {
     use std::error::Error;
 
-    use crate::{infer_csv, infer_csv_with_options, CsvInput, DataType, InferOptions};
+    use crate::{
+        infer_csv, infer_csv_with_options, CsvInput, DataType, Encoding, InferOptions,
+        InferSession, COMMON_NULL_TOKENS,
+    };
 
     #[test]
     fn default() -> Result<(), Box<dyn Error>> {
@@ -181,4 +371,73 @@ mod test {
         );
         Ok(())
     }
+
+    #[test]
+    fn bytes() -> Result<(), Box<dyn Error>> {
+        let fields = infer_csv(CsvInput::Bytes(b"0,a\n1,b"))?;
+        assert_eq!(2, fields.len());
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+
+        let fields = infer_csv(CsvInput::Reader(Box::new(&b"0.5,a\n1,b"[..])))?;
+        assert!(matches!(fields[0].valid_types[0], DataType::Float(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn nulls() -> Result<(), Box<dyn Error>> {
+        let fields = infer_csv_with_options(
+            CsvInput::Value("1,2,3\nNA,,4\n\\N,5,6\n"),
+            &mut InferOptions {
+                null_tokens: COMMON_NULL_TOKENS.map(String::from).to_vec(),
+                ..InferOptions::default()
+            },
+        )?;
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+        assert_eq!(vec!["NA", "\\N"], fields[0].null_tokens);
+        assert_eq!(vec![""], fields[1].null_tokens);
+        assert!(fields[1].nullable);
+        assert!(fields[2].null_tokens.is_empty());
+        assert!(!fields[2].nullable);
+        assert!(format!("{fields:#}").contains(r#"null: "NA", "\\N""#));
+        Ok(())
+    }
+
+    #[test]
+    fn malformed() -> Result<(), Box<dyn Error>> {
+        let input = b"id,name\n1,Ferris\n2\n3,\xff\n4,Corro\n";
+        let mut options = InferOptions {
+            has_headers: true,
+            // Otherwise taken for Windows-1252 with the `encoding` feature.
+            encoding: Some(Encoding::Utf8),
+            max_errors: Some(2),
+            ..InferOptions::default()
+        };
+        let fields = infer_csv_with_options(CsvInput::Bytes(input), &mut options)?;
+        assert_eq!(2, fields.report.records);
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+
+        let rejected = &fields.report.rejected;
+        assert_eq!(2, rejected.len());
+        let position = rejected[0].position.as_ref().unwrap();
+        assert_eq!((17, 3), (position.byte(), position.line()));
+        assert!(rejected[0].error.contains("1 fields"));
+        assert_eq!(4, rejected[1].position.as_ref().unwrap().line());
+
+        let mut session = InferSession::new(&options);
+        session.push_bytes(input)?;
+        let fields = session.finish()?;
+        assert_eq!(
+            Some(19),
+            fields.report.rejected[1]
+                .position
+                .as_ref()
+                .map(|p| p.byte())
+        );
+
+        options.max_errors = Some(1);
+        assert!(infer_csv_with_options(CsvInput::Bytes(input), &mut options).is_err());
+        options.max_errors = None;
+        assert!(infer_csv_with_options(CsvInput::Bytes(input), &mut options).is_err());
+        Ok(())
+    }
 }
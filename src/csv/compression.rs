@@ -0,0 +1,132 @@
+use std::{
+    error::Error,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+/// A compression format that input can be transparently decompressed from.
+///
+/// Each codec other than [`Compression::None`] is behind a cargo feature named
+/// after it in lowercase, so the default build stays small.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Compression {
+    fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+            Compression::Xz => "xz",
+        }
+    }
+
+    /// Detects the compression of input starting with `magic`, falling back to
+    /// the extension of `path` when the magic bytes are inconclusive.
+    pub fn detect(magic: &[u8], path: Option<&str>) -> Compression {
+        match magic {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Compression::Zstd,
+            [0xfd, b'7', b'z', b'X', b'Z', 0x00, ..] => Compression::Xz,
+            // A block or end of stream magic number must follow, which keeps
+            // plain text starting with "BZh" from being taken for bzip2.
+            [b'B', b'Z', b'h', b'1'..=b'9', 0x31, 0x41, 0x59, 0x26, 0x53, 0x59, ..]
+            | [b'B', b'Z', b'h', b'1'..=b'9', 0x17, 0x72, 0x45, 0x38, 0x50, 0x90, ..] => {
+                Compression::Bzip2
+            }
+            _ if magic.len() < MAGIC_LEN => path
+                .and_then(|path| Path::new(path).extension())
+                .and_then(|extension| extension.to_str())
+                .map_or(Compression::None, |extension| match extension {
+                    "gz" | "gzip" => Compression::Gzip,
+                    "zst" | "zstd" => Compression::Zstd,
+                    "bz2" => Compression::Bzip2,
+                    "xz" => Compression::Xz,
+                    _ => Compression::None,
+                }),
+            _ => Compression::None,
+        }
+    }
+}
+
+const MAGIC_LEN: usize = 10;
+
+/// Wraps `reader` in a decoder for `compression`, detecting it from the first
+/// few bytes (and `path`) when not given.
+pub(crate) fn decompress<'a>(
+    mut reader: Box<dyn Read + 'a>,
+    path: Option<&str>,
+    compression: Option<Compression>,
+) -> Result<Box<dyn Read + 'a>, Box<dyn Error>> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    (&mut reader)
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut magic)?;
+    let compression = compression.unwrap_or_else(|| Compression::detect(&magic, path));
+    let reader = Box::new(Cursor::new(magic).chain(reader));
+
+    match compression {
+        Compression::None => Ok(reader),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => Ok(Box::new(zstd::Decoder::new(reader)?)),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Ok(Box::new(bzip2::read::MultiBzDecoder::new(reader))),
+        #[cfg(feature = "xz")]
+        Compression::Xz => Ok(Box::new(xz2::read::XzDecoder::new_multi_decoder(reader))),
+        #[allow(unreachable_patterns)]
+        compression => Err(format!(
+            "input is {} compressed, but the `{0}` feature is disabled",
+            compression.name()
+        )
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::csv::Compression;
+
+    #[test]
+    fn detect() {
+        assert_eq!(
+            Compression::Gzip,
+            Compression::detect(&[0x1f, 0x8b, 0x08, 0, 0, 0, 0, 0, 0, 0], None)
+        );
+        assert_eq!(
+            Compression::None,
+            Compression::detect(b"BZh,a,b,c\n1", Some("data.csv.bz2"))
+        );
+        assert_eq!(
+            Compression::Zstd,
+            Compression::detect(b"", Some("data.csv.zst"))
+        );
+        assert_eq!(
+            Compression::None,
+            Compression::detect(b"a,b\n", Some("data.csv"))
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() -> Result<(), Box<dyn std::error::Error>> {
+        use std::io::Write;
+
+        use crate::{infer_csv, CsvInput, DataType};
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(b"0,a\n1,b\n")?;
+        let fields = infer_csv(CsvInput::Bytes(&encoder.finish()?))?;
+        assert_eq!(2, fields.len());
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{Cursor, Read},
+};
+
+/// How many bytes at the start of the input [`Encoding::detect`] looks at.
+pub const ENCODING_SAMPLE_LEN: usize = 64 * 1024;
+
+const UTF_8_BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// A text encoding that input can be transcoded to UTF-8 from.
+///
+/// Only [`Encoding::Utf8`] is read without the `encoding` cargo feature, though
+/// a UTF-8 byte order mark is always skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Windows-1252, which also covers the printable characters of Latin-1
+    /// (ISO-8859-1).
+    Windows1252,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "Windows-1252",
+        })
+    }
+}
+
+impl Encoding {
+    /// Detects the encoding of input starting with `sample`, by its byte order
+    /// mark or else by the bytes themselves.
+    ///
+    /// Without a byte order mark, text that is mostly ASCII in UTF-16 has a
+    /// zero byte in every other place. Otherwise, a sample that is not valid
+    /// UTF-8 is taken to be Windows-1252.
+    pub fn detect(sample: &[u8]) -> Encoding {
+        match sample {
+            [0xef, 0xbb, 0xbf, ..] => return Encoding::Utf8,
+            [0xff, 0xfe, ..] => return Encoding::Utf16Le,
+            [0xfe, 0xff, ..] => return Encoding::Utf16Be,
+            _ => (),
+        }
+        let zeros = |parity| {
+            sample
+                .iter()
+                .skip(parity)
+                .step_by(2)
+                .filter(|&&byte| byte == 0)
+                .count()
+        };
+        let (even, odd) = (zeros(0), zeros(1));
+        let half = sample.len() / 2;
+        if half > 0 && odd > half / 2 && even <= half / 8 {
+            return Encoding::Utf16Le;
+        }
+        if half > 0 && even > half / 2 && odd <= half / 8 {
+            return Encoding::Utf16Be;
+        }
+        match std::str::from_utf8(sample) {
+            Ok(_) => Encoding::Utf8,
+            // The sample may end part way through a character.
+            Err(err) if err.error_len().is_none() => Encoding::Utf8,
+            Err(_) => Encoding::Windows1252,
+        }
+    }
+}
+
+/// Wraps `reader` in a decoder from `encoding` to UTF-8, detecting it from the
+/// first [`ENCODING_SAMPLE_LEN`] bytes when not given, and skipping any byte
+/// order mark. Gives the encoding the input is read in.
+pub(crate) fn transcode<'a>(
+    mut reader: Box<dyn Read + 'a>,
+    encoding: Option<Encoding>,
+) -> Result<(Box<dyn Read + 'a>, Encoding), Box<dyn Error>> {
+    let mut sample = Vec::with_capacity(ENCODING_SAMPLE_LEN);
+    (&mut reader)
+        .take(ENCODING_SAMPLE_LEN as u64)
+        .read_to_end(&mut sample)?;
+    let detected = encoding.unwrap_or_else(|| Encoding::detect(&sample));
+    if detected == Encoding::Utf8 && sample.starts_with(UTF_8_BOM) {
+        sample.drain(..UTF_8_BOM.len());
+    }
+    let reader: Box<dyn Read + 'a> = Box::new(Cursor::new(sample).chain(reader));
+
+    match detected {
+        Encoding::Utf8 => Ok((reader, detected)),
+        #[cfg(feature = "encoding")]
+        _ => {
+            let encoding = match detected {
+                Encoding::Utf16Le => encoding_rs::UTF_16LE,
+                Encoding::Utf16Be => encoding_rs::UTF_16BE,
+                _ => encoding_rs::WINDOWS_1252,
+            };
+            let reader = encoding_rs_io::DecodeReaderBytesBuilder::new()
+                .encoding(Some(encoding))
+                .strip_bom(true)
+                .build(reader);
+            Ok((Box::new(reader), detected))
+        }
+        // Without a decoder, input that only looks to be Windows-1252 is read
+        // as UTF-8, so that records that are not valid UTF-8 are rejected
+        // one at a time.
+        #[cfg(not(feature = "encoding"))]
+        Encoding::Windows1252 if encoding.is_none() => Ok((reader, Encoding::Utf8)),
+        #[cfg(not(feature = "encoding"))]
+        _ => Err(
+            format!("input is {detected} encoded, but the `encoding` feature is disabled").into(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{csv::Encoding, infer_csv_with_options, CsvInput, InferOptions};
+
+    #[test]
+    fn detect() {
+        assert_eq!(Encoding::Utf8, Encoding::detect(b"\xef\xbb\xbfid\n1\n"));
+        assert_eq!(
+            Encoding::Utf8,
+            Encoding::detect("name\nJos\u{e9}\n".as_bytes())
+        );
+        assert_eq!(Encoding::Utf16Le, Encoding::detect(b"i\0d\0\n\x001\0\n\0"));
+        assert_eq!(Encoding::Utf16Be, Encoding::detect(b"\xfe\xff\0i\0d"));
+        assert_eq!(Encoding::Windows1252, Encoding::detect(b"name\nJos\xe9\n"));
+    }
+
+    #[test]
+    fn bom() -> Result<(), Box<dyn Error>> {
+        let fields = infer_csv_with_options(
+            CsvInput::Bytes(b"\xef\xbb\xbfid,name\n1,Ferris\n"),
+            &mut InferOptions {
+                has_headers: true,
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(Some("id".into()), fields[0].name);
+        assert_eq!(Some(Encoding::Utf8), fields.report.encoding);
+        Ok(())
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn transcode() -> Result<(), Box<dyn Error>> {
+        let mut options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let fields =
+            infer_csv_with_options(CsvInput::Bytes(b"caf\xe9,n\nCr\xe8me,1\n"), &mut options)?;
+        assert_eq!(Some("caf\u{e9}".into()), fields[0].name);
+        assert_eq!(Some(Encoding::Windows1252), fields.report.encoding);
+
+        let value: Vec<u8> = "\u{feff}id,name\n1,Jos\u{e9}\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let fields = infer_csv_with_options(CsvInput::Bytes(&value), &mut options)?;
+        assert_eq!(Some("id".into()), fields[0].name);
+        assert_eq!(Some(Encoding::Utf16Le), fields.report.encoding);
+        Ok(())
+    }
+}
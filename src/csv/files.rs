@@ -0,0 +1,206 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    csv::{infer_csv_with_options, CsvInput, InferOptions},
+    field::Fields,
+};
+
+/// The fields inferred from one file by [`infer_csv_files`].
+#[derive(Debug, Clone)]
+pub struct FileFields {
+    pub path: PathBuf,
+    pub fields: Fields,
+}
+
+/// A file whose column names differ from those of the first file.
+#[derive(Debug, Clone)]
+pub struct HeaderMismatch {
+    pub path: PathBuf,
+    /// Columns of the first file this one lacks.
+    pub missing: Vec<String>,
+    /// Columns of this file the first one lacks.
+    pub extra: Vec<String>,
+}
+
+/// The outcome of [`infer_csv_files`].
+#[derive(Debug, Clone)]
+pub struct FilesInference {
+    /// Each file in path order, along with its fields.
+    pub files: Vec<FileFields>,
+    /// The [union](Fields::union) of the fields of every file.
+    pub combined: Fields,
+    pub mismatches: Vec<HeaderMismatch>,
+}
+
+/// Infers every file in a directory, or every file matching a glob pattern
+/// such as `exports/*/part-*.csv.gz`, with the same options.
+///
+/// Of a directory, only files with one of [`TABLE_EXTENSIONS`], possibly
+/// compressed as `.csv.gz` is, are inferred. Empty files and those whose names
+/// start with `_` or `.`, such as the `_SUCCESS` markers of partitioned
+/// exports, are skipped.
+///
+/// Column names are only compared, and columns only matched by name, when
+/// [`InferOptions::has_headers`] or [`InferOptions::detect_headers`] is set.
+pub fn infer_csv_files(
+    pattern: &str,
+    options: &mut InferOptions,
+) -> Result<FilesInference, Box<dyn Error>> {
+    let mut paths = if Path::new(pattern).is_dir() {
+        fs::read_dir(pattern)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| is_table(path))
+            .collect()
+    } else {
+        glob::glob(pattern)?
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>()
+    };
+    if paths.is_empty() {
+        return Err(format!("no files match {pattern}").into());
+    }
+    paths.sort();
+
+    let mut files = vec![];
+    for path in paths {
+        let name = path
+            .to_str()
+            .ok_or_else(|| format!("{} is not valid UTF-8", path.display()))?;
+        let fields = infer_csv_with_options(CsvInput::Path(name), options)
+            .map_err(|err| format!("{name}: {err}"))?;
+        files.push(FileFields { path, fields });
+    }
+
+    let names = |fields: &Fields| -> Vec<String> {
+        fields
+            .iter()
+            .filter_map(|field| field.name.clone())
+            .collect()
+    };
+    let first = names(&files[0].fields);
+    let mismatches = files
+        .iter()
+        .skip(1)
+        .filter_map(|file| {
+            let names = names(&file.fields);
+            let missing: Vec<_> = first
+                .iter()
+                .filter(|name| !names.contains(name))
+                .cloned()
+                .collect();
+            let extra: Vec<_> = names
+                .iter()
+                .filter(|name| !first.contains(name))
+                .cloned()
+                .collect();
+            (!missing.is_empty() || !extra.is_empty()).then(|| HeaderMismatch {
+                path: file.path.clone(),
+                missing,
+                extra,
+            })
+        })
+        .collect();
+
+    Ok(FilesInference {
+        combined: Fields::union(files.iter().map(|file| file.fields.clone())),
+        files,
+        mismatches,
+    })
+}
+
+/// Extensions of the files of a directory that [`infer_csv_files`] infers.
+pub const TABLE_EXTENSIONS: &[&str] = &["csv", "tsv", "psv", "tab"];
+
+const COMPRESSED_EXTENSIONS: &[&str] = &["gz", "gzip", "zst", "zstd", "bz2", "xz"];
+
+/// Whether the entry of a directory at `path` is a file of records to infer.
+fn is_table(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| !name.starts_with(['_', '.']) && has_table_extension(name))
+        && fs::metadata(path).is_ok_and(|metadata| metadata.is_file() && metadata.len() > 0)
+}
+
+/// Whether `name` ends in one of [`TABLE_EXTENSIONS`], or one of them followed
+/// by that of a compression format.
+fn has_table_extension(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let mut extensions = name.rsplit('.');
+    let (last, previous, rest) = (extensions.next(), extensions.next(), extensions.next());
+    let is_table = |extension: Option<&str>| {
+        extension.is_some_and(|extension| TABLE_EXTENSIONS.contains(&extension))
+    };
+    match (last, previous) {
+        (Some(last), _) if COMPRESSED_EXTENSIONS.contains(&last) => {
+            is_table(previous) && rest.is_some()
+        }
+        (last, previous) => is_table(last) && previous.is_some(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{error::Error, fs};
+
+    use crate::{
+        csv::{files::has_table_extension, infer_csv_files},
+        DataType, InferOptions,
+    };
+
+    #[test]
+    fn directory() -> Result<(), Box<dyn Error>> {
+        let dir = std::env::temp_dir().join(format!("baskerville-files-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join("a.csv"), "id,score\n1,2\n")?;
+        fs::write(dir.join("b.csv"), "id,score\n2,0.5\n")?;
+        fs::write(dir.join("c.csv"), "id,name\n3,Ferris\n")?;
+        fs::write(dir.join("_SUCCESS"), "")?;
+        fs::write(dir.join("_manifest.csv"), "path\na.csv\n")?;
+        fs::write(dir.join("README.txt"), "Nightly export\n")?;
+        fs::write(dir.join("empty.csv"), "")?;
+
+        let mut options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let result = infer_csv_files(dir.to_str().unwrap(), &mut options);
+        let glob = infer_csv_files(dir.join("[ab].csv").to_str().unwrap(), &mut options);
+        fs::remove_dir_all(&dir)?;
+
+        let inference = result?;
+        assert_eq!(3, inference.files.len());
+        assert_eq!(1, inference.mismatches.len());
+        assert!(inference.mismatches[0].path.ends_with("c.csv"));
+        assert_eq!(vec!["score"], inference.mismatches[0].missing);
+        assert_eq!(vec!["name"], inference.mismatches[0].extra);
+        assert!(inference.combined[1].optional);
+
+        let inference = glob?;
+        assert_eq!(2, inference.files.len());
+        assert!(inference.mismatches.is_empty());
+        assert!(matches!(
+            inference.combined[1].valid_types[0],
+            DataType::Float(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn extensions() {
+        assert!(has_table_extension("part-00000.csv"));
+        assert!(has_table_extension("part-00000.CSV.gz"));
+        assert!(has_table_extension("rates.tsv.zst"));
+        assert!(!has_table_extension("notes.gz"));
+        assert!(!has_table_extension("manifest.json"));
+        assert!(!has_table_extension("csv"));
+        assert!(!has_table_extension("csv.gz"));
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use csv::{ByteRecord, Position};
+
+use crate::csv::InferOptions;
+
+/// How many records are looked at to find where the table starts, when
+/// [`InferOptions::detect_preamble`] is set.
+pub const PREAMBLE_SAMPLE_LEN: usize = 100;
+
+/// How many records in a row must have the width of the table for the first
+/// of them to be taken as its start.
+const TABLE_RUN: usize = 3;
+
+/// Picks the table out of the records of the input, dropping skipped lines,
+/// records before the header row and, when detected, a preamble and footer
+/// of a different shape from the table.
+///
+/// [`Frame::push`] lets a record straight through where it can. Otherwise the
+/// record is held back, and whatever is ready to go on is given by
+/// [`Frame::pop`], in input order.
+#[derive(Debug, Clone)]
+pub(crate) struct Frame {
+    skip_lines: u64,
+    header_row: usize,
+    detect_footer: bool,
+    /// Records seen, including skipped ones.
+    seen: u64,
+    /// Records held back to find the start of the table.
+    head: Option<Vec<ByteRecord>>,
+    width: Option<usize>,
+    /// Records not of the width of the table, dropped as the footer unless
+    /// another record of the right width turns up.
+    tail: Vec<ByteRecord>,
+    ready: VecDeque<ByteRecord>,
+    /// Records dropped before the table.
+    pub(crate) preamble: u64,
+    footer: u64,
+}
+
+impl Frame {
+    pub(crate) fn new(options: &InferOptions) -> Self {
+        Frame {
+            skip_lines: options.skip_lines,
+            header_row: options.header_row,
+            detect_footer: options.detect_footer,
+            seen: 0,
+            head: options.detect_preamble.then(Vec::new),
+            width: None,
+            tail: vec![],
+            ready: VecDeque::new(),
+            preamble: 0,
+            footer: 0,
+        }
+    }
+
+    /// A frame that lets every record through, for input that has already
+    /// been framed.
+    pub(crate) fn none() -> Self {
+        Frame::new(&InferOptions::default())
+    }
+
+    /// Records dropped as the footer, or held back as one were the input to
+    /// end here.
+    pub(crate) fn footer(&self) -> u64 {
+        self.footer + self.tail.len() as u64
+    }
+
+    /// Takes in the next record, returning whether it goes straight on. When
+    /// it does not, it is either dropped or held back.
+    pub(crate) fn push(&mut self, record: &ByteRecord) -> bool {
+        self.seen += 1;
+        let line = record.position().map_or(self.seen, Position::line);
+        if line <= self.skip_lines || self.header_row > 0 {
+            if line > self.skip_lines {
+                self.header_row -= 1;
+            }
+            self.preamble += 1;
+            return false;
+        }
+
+        if let Some(ref mut head) = self.head {
+            head.push(record.clone());
+            if head.len() >= PREAMBLE_SAMPLE_LEN {
+                self.settle_head();
+            }
+            return false;
+        }
+        self.pass(record)
+    }
+
+    /// Takes in the next record of the table, or of the footer.
+    fn pass(&mut self, record: &ByteRecord) -> bool {
+        let width = *self.width.get_or_insert(record.len());
+        if self.detect_footer && record.len() != width {
+            self.tail.push(record.clone());
+            return false;
+        }
+        if self.ready.is_empty() && self.tail.is_empty() {
+            return true;
+        }
+        self.ready.extend(self.tail.drain(..));
+        self.ready.push_back(record.clone());
+        false
+    }
+
+    /// Whether records are still being held back to find the start of the
+    /// table.
+    pub(crate) fn is_deciding(&self) -> bool {
+        self.head.is_some()
+    }
+
+    /// The next record held back that is ready to go on.
+    pub(crate) fn pop(&mut self) -> Option<ByteRecord> {
+        self.ready.pop_front()
+    }
+
+    /// Ends the input, making ready any records still held back to find the
+    /// start of the table and dropping the footer.
+    pub(crate) fn finish(&mut self) {
+        self.settle_head();
+        self.footer += self.tail.len() as u64;
+        self.tail.clear();
+    }
+
+    /// Drops the records held back before the start of the table, and pushes
+    /// the rest again.
+    fn settle_head(&mut self) {
+        let Some(head) = self.head.take() else {
+            return;
+        };
+        let start = table_start(&head);
+        self.preamble += start as u64;
+        for record in &head[start..] {
+            if self.pass(record) {
+                self.ready.push_back(record.clone());
+            }
+        }
+    }
+}
+
+/// Where the table starts among `records`: at the first run of records with
+/// the most common width, or at the last few records should they all have it.
+fn table_start(records: &[ByteRecord]) -> usize {
+    let mut counts = BTreeMap::<usize, usize>::new();
+    for record in records {
+        *counts.entry(record.len()).or_default() += 1;
+    }
+    let Some((&width, _)) = counts.iter().max_by_key(|&(&width, &count)| (count, width)) else {
+        return 0;
+    };
+    (0..records.len())
+        .find(|&i| {
+            records[i..]
+                .iter()
+                .take(TABLE_RUN)
+                .all(|record| record.len() == width)
+        })
+        .unwrap_or_default()
+}
+
+/// Frames `records` as they are read, ending the frame when they run out.
+pub(crate) fn frame_records<'a>(
+    frame: &'a mut Frame,
+    mut records: impl Iterator<Item = csv::Result<ByteRecord>> + 'a,
+) -> impl Iterator<Item = csv::Result<ByteRecord>> + 'a {
+    let mut done = false;
+    std::iter::from_fn(move || loop {
+        if let Some(record) = frame.pop() {
+            return Some(Ok(record));
+        }
+        if done {
+            return None;
+        }
+        match records.next() {
+            Some(Ok(record)) => {
+                if frame.push(&record) {
+                    return Some(Ok(record));
+                }
+            }
+            Some(err) => return Some(err),
+            None => {
+                frame.finish();
+                done = true;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{infer_csv_parallel, infer_csv_with_options, CsvInput, DataType, InferOptions};
+
+    #[test]
+    fn banners() -> Result<(), Box<dyn Error>> {
+        let value = "Sales report\nGenerated 2024-01-01\n# region: EU\nid,amount\n1,0.5\n2,3\n3,4\nTotal rows: 3\n";
+        let mut options = InferOptions {
+            has_headers: true,
+            comment: Some(b'#'),
+            detect_preamble: true,
+            detect_footer: true,
+            ..InferOptions::default()
+        };
+        for fields in [
+            infer_csv_with_options(CsvInput::Value(value), &mut options)?,
+            infer_csv_parallel(CsvInput::Value(value), &mut options, 2)?,
+        ] {
+            assert_eq!(Some("amount".into()), fields[1].name);
+            assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+            assert_eq!(3, fields.report.records);
+            assert_eq!((2, 1), (fields.report.preamble, fields.report.footer));
+        }
+
+        let fields = infer_csv_with_options(
+            CsvInput::Value("Sales report\nGenerated 2024-01-01\n# region: EU\nid,amount\n1,0.5\n"),
+            &mut InferOptions {
+                has_headers: true,
+                comment: Some(b'#'),
+                skip_lines: 1,
+                header_row: 1,
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(Some("id".into()), fields[0].name);
+        assert_eq!(2, fields.report.preamble);
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashSet;
+
+use csv::StringRecord;
+
+use crate::{
+    csv::{is_null, InferOptions},
+    Field,
+};
+
+/// How many records [`InferOptions::detect_headers`] looks at before deciding.
+pub const HEADER_SAMPLE_LEN: usize = 100;
+
+/// How headers are rewritten into field names, for
+/// [`InferOptions::header_case`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HeaderCase {
+    /// Leaves headers as they are.
+    #[default]
+    Keep,
+    /// `Joined At` becomes `joined at`.
+    Lower,
+    /// `Joined At`, `joinedAt` and `joined-at` all become `joined_at`.
+    Snake,
+}
+
+/// Rewrites `header` in the given case.
+pub fn normalize_header(header: &str, case: HeaderCase) -> String {
+    match case {
+        HeaderCase::Keep => header.to_string(),
+        HeaderCase::Lower => header.to_lowercase(),
+        HeaderCase::Snake => {
+            let chars: Vec<_> = header.chars().collect();
+            let mut snake = String::new();
+            for (i, &c) in chars.iter().enumerate() {
+                if c.is_alphanumeric() {
+                    // A word starts at an upper case letter after a lower case
+                    // one or a digit, or at the last of a run of capitals
+                    // before a lower case letter, as in `HTTPServer`.
+                    let boundary = c.is_uppercase()
+                        && i > 0
+                        && (chars[i - 1].is_lowercase()
+                            || chars[i - 1].is_numeric()
+                            || chars[i - 1].is_uppercase()
+                                && chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+                    if boundary && !snake.is_empty() && !snake.ends_with('_') {
+                        snake.push('_');
+                    }
+                    snake.extend(c.to_lowercase());
+                } else if !snake.is_empty() && !snake.ends_with('_') {
+                    snake.push('_');
+                }
+            }
+            snake.truncate(snake.trim_end_matches('_').len());
+            snake
+        }
+    }
+}
+
+/// Appends `_2`, `_3` and so on to each name already taken by an earlier
+/// field, skipping suffixes that would clash with another name.
+pub(crate) fn dedupe_names(fields: &mut [Field]) {
+    let mut taken: HashSet<String> = fields
+        .iter()
+        .filter_map(|field| field.name.clone())
+        .collect();
+    let mut seen = HashSet::new();
+    for name in fields.iter_mut().filter_map(|field| field.name.as_mut()) {
+        if !seen.insert(name.clone()) {
+            let suffixed = (2..)
+                .map(|n| format!("{name}_{n}"))
+                .find(|suffixed| !taken.contains(suffixed))
+                .unwrap();
+            taken.insert(suffixed.clone());
+            seen.insert(suffixed.clone());
+            *name = suffixed;
+        }
+    }
+}
+
+/// The name given to the column at `position` when it has no header, counting
+/// from 1.
+pub(crate) fn missing_header_name(position: usize) -> String {
+    format!("column_{}", position + 1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderVote {
+    /// The rest of the column has a type the first value does not.
+    Header,
+    /// The first value has the same specific type as the rest of the column.
+    Data,
+    /// The column is all text, or empty, so it says nothing either way.
+    Neutral,
+}
+
+/// The evidence one column gives on whether the first row is a header.
+#[derive(Debug, Clone)]
+pub struct HeaderEvidence {
+    /// The value in the first row.
+    pub value: String,
+    /// Names of the types the first value is valid for.
+    pub value_types: Vec<&'static str>,
+    /// Names of the types valid for the rest of the column.
+    pub column_types: Vec<&'static str>,
+    pub vote: HeaderVote,
+}
+
+/// Whether the first row was taken as a header, and why.
+#[derive(Debug, Clone)]
+pub struct HeaderDetection {
+    pub has_headers: bool,
+    pub columns: Vec<HeaderEvidence>,
+}
+
+/// Decides whether the first of `records` is a header by comparing its type
+/// profile against the rest of the records, column by column.
+///
+/// For example, a first row of text over columns of integers and dates is
+/// almost certainly a header. The first row is taken as a header when more
+/// columns vote for it than against.
+pub fn detect_headers(records: &[StringRecord], options: &InferOptions) -> HeaderDetection {
+    let Some((first, rest)) = records.split_first() else {
+        return HeaderDetection {
+            has_headers: false,
+            columns: vec![],
+        };
+    };
+    let mut null_validator = options.null_validator.clone();
+
+    let columns: Vec<_> = first
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let value_types: Vec<_> = if is_null(options, &mut null_validator, value) {
+                vec![]
+            } else {
+                let mut field = Field::new(None, options.data_types.clone());
+                field.consider(value);
+                field.valid_types.iter().map(|t| t.name()).collect()
+            };
+
+            let mut field = Field::new(None, options.data_types.clone());
+            let mut values = 0;
+            for value in rest.iter().filter_map(|record| record.get(i)) {
+                if !is_null(options, &mut null_validator, value) {
+                    field.consider(value);
+                    values += 1;
+                }
+            }
+            let column_types: Vec<_> = if values > 0 {
+                field.valid_types.iter().map(|t| t.name()).collect()
+            } else {
+                vec![]
+            };
+
+            let specific: Vec<_> = column_types
+                .iter()
+                .filter(|name| **name != "Text")
+                .collect();
+            let vote = if value_types.is_empty() || specific.is_empty() {
+                HeaderVote::Neutral
+            } else if specific.iter().any(|name| value_types.contains(name)) {
+                HeaderVote::Data
+            } else {
+                HeaderVote::Header
+            };
+
+            HeaderEvidence {
+                value: value.to_string(),
+                value_types,
+                column_types,
+                vote,
+            }
+        })
+        .collect();
+
+    let votes = |vote| columns.iter().filter(|column| column.vote == vote).count();
+    HeaderDetection {
+        has_headers: votes(HeaderVote::Header) > votes(HeaderVote::Data),
+        columns,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{
+        csv::{detect_headers, normalize_header, HeaderVote},
+        infer_csv_with_options, CsvInput, HeaderCase, InferOptions,
+    };
+
+    #[test]
+    fn detect() {
+        let records = [
+            csv::StringRecord::from(vec!["Name", "LOC", "Joined"]),
+            csv::StringRecord::from(vec!["Ferris", "42", "2015-05-15"]),
+            csv::StringRecord::from(vec!["Corro", "7", ""]),
+        ];
+        let detection = detect_headers(&records, &InferOptions::default());
+        assert!(detection.has_headers);
+        assert_eq!(HeaderVote::Neutral, detection.columns[0].vote);
+        assert_eq!(HeaderVote::Header, detection.columns[1].vote);
+        assert_eq!(HeaderVote::Header, detection.columns[2].vote);
+
+        let detection = detect_headers(&records[1..], &InferOptions::default());
+        assert!(!detection.has_headers);
+    }
+
+    #[test]
+    fn infer() -> Result<(), Box<dyn Error>> {
+        let fields = infer_csv_with_options(
+            CsvInput::Value("Name,LOC\nFerris,42\nCorro,7"),
+            &mut InferOptions {
+                detect_headers: true,
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(Some("LOC".into()), fields[1].name);
+        assert!(fields.report.header.unwrap().has_headers);
+        Ok(())
+    }
+
+    #[test]
+    fn normalize() -> Result<(), Box<dyn Error>> {
+        for (header, snake) in [
+            ("Joined At", "joined_at"),
+            ("joinedAt", "joined_at"),
+            ("HTTPServer", "http_server"),
+            ("  LOC (2024) ", "loc_2024"),
+            ("#", ""),
+        ] {
+            assert_eq!(snake, normalize_header(header, HeaderCase::Snake));
+        }
+
+        let fields = infer_csv_with_options(
+            CsvInput::Value("Id, First Name ,,id,id_2\n1,Ferris,x,2,3\n"),
+            &mut InferOptions {
+                has_headers: true,
+                trim: csv::Trim::Headers,
+                header_case: HeaderCase::Snake,
+                dedupe_headers: true,
+                name_missing_headers: true,
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(
+            vec!["id", "first_name", "column_3", "id_3", "id_2"],
+            fields
+                .iter()
+                .map(|field| field.name.as_deref().unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some(" First Name ".into()), fields[1].header);
+        Ok(())
+    }
+}
@@ -0,0 +1,222 @@
+use std::{error::Error, io::Read, panic, sync::mpsc, thread};
+
+use csv::{ByteRecord, Position, Reader, StringRecord};
+
+use crate::{
+    csv::{
+        csv_reader, detect_headers,
+        frame::{frame_records, Frame},
+        progress::Tracker,
+        sampling::{keep, Sample, Sampler},
+        session::too_many_errors,
+        CsvInput, InferOptions, InferSession, HEADER_SAMPLE_LEN,
+    },
+    field::{Field, Fields},
+};
+
+const BATCH_SIZE: usize = 1024;
+
+/// Infers `input` across `threads` worker threads, giving the same fields as
+/// [`infer_csv_with_options`](crate::infer_csv_with_options).
+///
+/// Records are read on the calling thread and handed out to the workers in
+/// batches, so the input is only ever split at record boundaries. Each worker
+/// infers its own batches and the results are merged once the input runs out.
+pub fn infer_csv_parallel(
+    input: CsvInput,
+    options: &mut InferOptions,
+    threads: usize,
+) -> Result<Fields, Box<dyn Error>> {
+    let (mut reader, encoding) = csv_reader(input, options)?;
+    let mut fields = infer_csv_parallel_with_reader(options, &mut reader, threads.max(1))?;
+    fields.report.encoding = Some(encoding);
+    Ok(fields)
+}
+
+fn infer_csv_parallel_with_reader<R>(
+    options: &InferOptions,
+    reader: &mut Reader<R>,
+    threads: usize,
+) -> Result<Fields, Box<dyn Error>>
+where
+    R: Read,
+{
+    let mut frame = Frame::new(options);
+    let mut records = frame_records(&mut frame, reader.byte_records());
+    let mut sample = Vec::new();
+    let sample_len = if options.detect_headers {
+        HEADER_SAMPLE_LEN
+    } else {
+        1
+    };
+    for record in records.by_ref().take(sample_len) {
+        sample.push(record?);
+    }
+    let headers = match sample.first() {
+        Some(headers) => StringRecord::from_byte_record(headers.clone())?,
+        None => return Ok(Fields::default()),
+    };
+    let detection = if options.detect_headers {
+        // Records that are not valid UTF-8 are left for the workers to reject.
+        let sample: Vec<_> = sample
+            .iter()
+            .cloned()
+            .filter_map(|record| StringRecord::from_byte_record(record).ok())
+            .collect();
+        Some(detect_headers(&sample, options))
+    } else {
+        None
+    };
+    let has_headers = detection
+        .as_ref()
+        .map_or(options.has_headers, |detection| detection.has_headers);
+
+    let (read, incomplete, results) = thread::scope(|scope| {
+        let (senders, workers): (Vec<_>, Vec<_>) = (0..threads)
+            .map(|_| {
+                let (sender, receiver) = mpsc::sync_channel::<Vec<ByteRecord>>(2);
+                let headers = &headers;
+                let worker = scope.spawn(move || -> Result<Fields, String> {
+                    let mut session = InferSession::new(options).into_part();
+                    session.push_headers(headers, has_headers);
+                    for record in receiver.into_iter().flatten() {
+                        session
+                            .push_byte_record(record)
+                            .map_err(|e| e.to_string())?;
+                    }
+                    session.finish().map_err(|e| e.to_string())
+                });
+                (sender, worker)
+            })
+            .unzip();
+
+        // Records are sampled here rather than by the workers, since each only
+        // sees some of them.
+        let mut sampler = Sampler::new(options.sampling);
+        let mut reservoir = vec![];
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let mut next_sender = senders.iter().cycle();
+        let mut read = Ok(());
+        let mut incomplete = false;
+        let mut tracker = Tracker::new(options.progress.as_ref());
+        let sample = sample.into_iter().skip(has_headers as usize).map(Ok);
+        for record in sample.chain(records.by_ref()) {
+            if sampler.done() {
+                break;
+            }
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    read = Err(err);
+                    break;
+                }
+            };
+            let byte = record.position().map(Position::byte);
+            match sampler.sample(byte) {
+                Sample::Consider => batch.push(record),
+                Sample::Skip => (),
+                Sample::Keep(slot) => keep(&mut reservoir, slot, record),
+            }
+            if batch.len() == BATCH_SIZE {
+                let batch = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+                // A worker only hangs up after failing, and its error is
+                // reported when it is joined.
+                if next_sender.next().unwrap().send(batch).is_err() {
+                    break;
+                }
+            }
+            if tracker.record(byte.unwrap_or_default()).is_break() {
+                incomplete = true;
+                break;
+            }
+        }
+        batch.extend(reservoir);
+        let _ = next_sender.next().unwrap().send(batch);
+        drop(senders);
+
+        let results: Vec<_> = workers
+            .into_iter()
+            .map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|err| panic::resume_unwind(err))
+            })
+            .collect();
+        (read, incomplete, results)
+    });
+    read?;
+    drop(records);
+
+    let mut results = results.into_iter();
+    let mut fields = results.next().unwrap()?;
+    for result in results {
+        fields.merge(result?);
+    }
+    // Each worker kept to the error budget alone, but not necessarily together.
+    if let Some(max) = options.max_errors {
+        if fields.report.rejected.len() > max {
+            let last = fields.report.rejected.pop().unwrap();
+            return Err(too_many_errors(max, last.error.into()));
+        }
+    }
+    fields.iter_mut().for_each(Field::settle);
+    fields.report.header = detection;
+    fields.report.incomplete = incomplete;
+    fields.report.preamble = frame.preamble;
+    fields.report.footer = frame.footer();
+    fields.report.sampling = options.sampling;
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{
+        infer_csv_parallel, infer_csv_with_options, CsvInput, DataType, InferOptions, Integer,
+        Unique,
+    };
+
+    #[test]
+    fn sequential() -> Result<(), Box<dyn Error>> {
+        let mut value = String::from("id,score,name,joined\n");
+        for i in 0..5000 {
+            value.push_str(&format!(
+                "{i},{},n{i},2001-01-{:02}\n",
+                i as f64 / 2.0,
+                i % 28 + 1
+            ));
+        }
+        value.push_str("-1,,,\n");
+
+        let mut options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let sequential = infer_csv_with_options(CsvInput::Value(&value), &mut options)?;
+        let parallel = infer_csv_parallel(CsvInput::Value(&value), &mut options, 3)?;
+        assert_eq!(format!("{:?}", sequential), format!("{:?}", parallel));
+        Ok(())
+    }
+
+    #[test]
+    fn unique() -> Result<(), Box<dyn Error>> {
+        let mut value = (0..3000).map(|i| format!("{i}\n")).collect::<String>();
+        value.push_str("7\n");
+
+        let fields = infer_csv_parallel(
+            CsvInput::Value(&value),
+            &mut InferOptions {
+                data_types: vec![
+                    DataType::Unique(Unique::default()),
+                    DataType::Integer(Integer::default()),
+                ],
+                ..InferOptions::default()
+            },
+            2,
+        )?;
+        assert_eq!(1, fields[0].valid_types.len());
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+use std::{ops::ControlFlow, sync::Arc};
+
+/// How many records are read between calls to [`InferOptions::progress`](crate::InferOptions::progress).
+pub const PROGRESS_INTERVAL: u64 = 4096;
+
+/// How far through its input an inference is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Bytes of CSV read, after any decompression.
+    pub bytes: u64,
+    /// Records read, including any header row and records skipped by
+    /// sampling.
+    pub records: u64,
+}
+
+/// Told of the progress of an inference every [`PROGRESS_INTERVAL`] records.
+/// Returning [`ControlFlow::Break`] stops the inference early, which then
+/// gives the fields inferred so far, marked
+/// [incomplete](crate::Report::incomplete).
+pub type ProgressCallback = Arc<dyn Fn(Progress) -> ControlFlow<()> + Send + Sync>;
+
+pub(crate) struct Tracker<'a> {
+    callback: Option<&'a ProgressCallback>,
+    records: u64,
+}
+
+impl<'a> Tracker<'a> {
+    pub(crate) fn new(callback: Option<&'a ProgressCallback>) -> Self {
+        Tracker {
+            callback,
+            records: 0,
+        }
+    }
+
+    /// Counts another record read, up to `bytes` into the input, and reports
+    /// progress when due.
+    pub(crate) fn record(&mut self, bytes: u64) -> ControlFlow<()> {
+        self.records += 1;
+        match self.callback {
+            Some(callback) if self.records.is_multiple_of(PROGRESS_INTERVAL) => {
+                callback(Progress {
+                    bytes,
+                    records: self.records,
+                })
+            }
+            _ => ControlFlow::Continue(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        error::Error,
+        ops::ControlFlow,
+        sync::{Arc, Mutex},
+    };
+
+    use crate::{
+        csv::{Progress, PROGRESS_INTERVAL},
+        infer_csv_parallel, infer_csv_with_options, CsvInput, InferOptions,
+    };
+
+    #[test]
+    fn stop() -> Result<(), Box<dyn Error>> {
+        let value = (0..20000).map(|i| format!("{i}\n")).collect::<String>();
+        let calls = Arc::new(Mutex::new(vec![]));
+        let mut options = InferOptions {
+            progress: Some({
+                let calls = calls.clone();
+                Arc::new(move |progress: Progress| {
+                    let mut calls = calls.lock().unwrap();
+                    calls.push(progress);
+                    match calls.len() {
+                        2 => ControlFlow::Break(()),
+                        _ => ControlFlow::Continue(()),
+                    }
+                })
+            }),
+            ..InferOptions::default()
+        };
+
+        let fields = infer_csv_with_options(CsvInput::Value(&value), &mut options)?;
+        assert!(fields.report.incomplete);
+        assert_eq!(2 * PROGRESS_INTERVAL, fields.report.records);
+        let calls = std::mem::take(&mut *calls.lock().unwrap());
+        assert_eq!(PROGRESS_INTERVAL, calls[0].records);
+        assert!(calls[0].bytes > 4 * PROGRESS_INTERVAL);
+
+        let fields = infer_csv_parallel(CsvInput::Value(&value), &mut options, 2)?;
+        assert!(fields.report.incomplete);
+        assert_eq!(2 * PROGRESS_INTERVAL, fields.report.records);
+
+        options.progress = None;
+        let fields = infer_csv_with_options(CsvInput::Value(&value), &mut options)?;
+        assert!(!fields.report.incomplete);
+        Ok(())
+    }
+}
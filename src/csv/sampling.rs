@@ -0,0 +1,161 @@
+/// Which records of the input are considered. Anything other than
+/// [`SamplingStrategy::All`] trades accuracy for speed on large inputs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SamplingStrategy {
+    #[default]
+    All,
+    /// The first `n` records, after which the rest of the input is not read.
+    FirstRows(u64),
+    /// Records starting within the first `n` bytes, after which the rest of
+    /// the input is not read.
+    FirstBytes(u64),
+    /// Every `k`th record, starting with the first.
+    EveryNth(u64),
+    /// `size` records picked uniformly at random across the whole input.
+    /// The same `seed` picks the same records.
+    Reservoir { size: usize, seed: u64 },
+}
+
+pub(crate) enum Sample {
+    Consider,
+    Skip,
+    /// Keep the record in the reservoir, at the given slot.
+    Keep(usize),
+}
+
+pub(crate) struct Sampler {
+    strategy: SamplingStrategy,
+    seen: u64,
+    byte: u64,
+    rng: SplitMix64,
+}
+
+impl Sampler {
+    pub(crate) fn new(strategy: SamplingStrategy) -> Self {
+        Sampler {
+            strategy,
+            seen: 0,
+            byte: 0,
+            rng: SplitMix64(match strategy {
+                SamplingStrategy::Reservoir { seed, .. } => seed,
+                _ => 0,
+            }),
+        }
+    }
+
+    /// Decides what to do with the next record, which starts at `byte` when
+    /// its position is known.
+    pub(crate) fn sample(&mut self, byte: Option<u64>) -> Sample {
+        let index = self.seen;
+        self.seen += 1;
+        if let Some(byte) = byte {
+            self.byte = byte;
+        }
+
+        let consider = match self.strategy {
+            SamplingStrategy::All => true,
+            SamplingStrategy::FirstRows(n) => index < n,
+            SamplingStrategy::FirstBytes(n) => self.byte < n,
+            SamplingStrategy::EveryNth(k) => index.is_multiple_of(k.max(1)),
+            // Algorithm R: the `i`th record replaces a random slot with
+            // probability `size / i`.
+            SamplingStrategy::Reservoir { size, .. } => {
+                let slot = if index < size as u64 {
+                    index
+                } else {
+                    self.rng.below(index + 1)
+                };
+                return if slot < size as u64 {
+                    Sample::Keep(slot as usize)
+                } else {
+                    Sample::Skip
+                };
+            }
+        };
+        if consider {
+            Sample::Consider
+        } else {
+            Sample::Skip
+        }
+    }
+
+    /// Whether no later record will be considered, so the rest of the input
+    /// need not be read.
+    pub(crate) fn done(&self) -> bool {
+        match self.strategy {
+            SamplingStrategy::FirstRows(n) => self.seen >= n,
+            SamplingStrategy::FirstBytes(n) => self.byte >= n,
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn keep<T>(reservoir: &mut Vec<T>, slot: usize, record: T) {
+    if slot < reservoir.len() {
+        reservoir[slot] = record;
+    } else {
+        reservoir.push(record);
+    }
+}
+
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    fn below(&mut self, n: u64) -> u64 {
+        ((self.next() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{csv::SamplingStrategy, infer_csv_with_options, CsvInput, DataType, InferOptions};
+
+    fn sample(sampling: SamplingStrategy) -> Result<crate::Fields, Box<dyn Error>> {
+        let mut value = String::from("n\n");
+        for i in 0..1000 {
+            value.push_str(&format!("{i}\n"));
+        }
+        value.push_str("Ferris\n");
+        infer_csv_with_options(
+            CsvInput::Value(&value),
+            &mut InferOptions {
+                has_headers: true,
+                sampling,
+                ..InferOptions::default()
+            },
+        )
+    }
+
+    #[test]
+    fn strategies() -> Result<(), Box<dyn Error>> {
+        let fields = sample(SamplingStrategy::FirstRows(10))?;
+        assert_eq!(10, fields.report.records);
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+
+        let fields = sample(SamplingStrategy::FirstBytes(20))?;
+        assert_eq!(9, fields.report.records);
+
+        let fields = sample(SamplingStrategy::EveryNth(5))?;
+        assert_eq!(201, fields.report.records);
+        assert!(matches!(fields[0].valid_types[0], DataType::Text(_)));
+
+        let fields = sample(SamplingStrategy::Reservoir { size: 50, seed: 1 })?;
+        assert_eq!(50, fields.report.records);
+        assert_eq!(
+            SamplingStrategy::Reservoir { size: 50, seed: 1 },
+            fields.report.sampling
+        );
+        Ok(())
+    }
+}
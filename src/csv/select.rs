@@ -0,0 +1,130 @@
+use crate::DataType;
+
+/// Picks out a column of the input, for [`InferOptions::columns`](crate::InferOptions::columns).
+#[derive(Debug, Clone)]
+pub enum ColumnSelector {
+    /// The column at this index, counting from 0.
+    Index(usize),
+    /// The column with this header, after any trimming but before any other
+    /// [normalization](crate::InferOptions::header_case).
+    Name(String),
+    /// Columns with headers matching this pattern.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl ColumnSelector {
+    /// Whether this selects the column at `index`, with the given header if
+    /// there is a header row.
+    pub fn matches(&self, index: usize, header: Option<&str>) -> bool {
+        match self {
+            ColumnSelector::Index(i) => *i == index,
+            ColumnSelector::Name(name) => header == Some(name),
+            #[cfg(feature = "regex")]
+            ColumnSelector::Regex(regex) => header.is_some_and(|header| regex.is_match(header)),
+        }
+    }
+}
+
+/// Changes the candidate types of the columns picked out by a
+/// [`ColumnSelector`], for [`InferOptions::overrides`](crate::InferOptions::overrides).
+#[derive(Debug, Clone)]
+pub enum Override {
+    /// Tries these types instead of [`InferOptions::data_types`](crate::InferOptions::data_types).
+    Replace(Vec<DataType>),
+    /// Takes the column to be of this type, without validating any values.
+    Pin(DataType),
+    /// Tries these types ahead of the others.
+    Extend(Vec<DataType>),
+}
+
+impl From<usize> for ColumnSelector {
+    fn from(index: usize) -> Self {
+        ColumnSelector::Index(index)
+    }
+}
+
+impl From<&str> for ColumnSelector {
+    fn from(name: &str) -> Self {
+        ColumnSelector::Name(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{
+        csv::Override, infer_csv_with_options, CsvInput, DataType, InferOptions, Integer, Literal,
+        Text,
+    };
+
+    #[test]
+    fn columns() -> Result<(), Box<dyn Error>> {
+        let mut options = InferOptions {
+            has_headers: true,
+            columns: Some(vec!["score".into(), 0.into()]),
+            ..InferOptions::default()
+        };
+        let value = "id,name,score\n1,Ferris,0.5\n2,Corro,1\n";
+        let fields = infer_csv_with_options(CsvInput::Value(value), &mut options)?;
+        assert_eq!(2, fields.len());
+        assert_eq!(
+            vec![(Some("id"), Some(0)), (Some("score"), Some(2))],
+            fields
+                .iter()
+                .map(|field| (field.name.as_deref(), field.position))
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(fields[1].valid_types[0], DataType::Float(_)));
+
+        // Rows must still have every column, selected or not.
+        let value = "id,name,score\n1,Ferris,0.5\n2,Corro\n";
+        assert!(infer_csv_with_options(CsvInput::Value(value), &mut options).is_err());
+
+        #[cfg(feature = "regex")]
+        {
+            options.columns = Some(vec![crate::csv::ColumnSelector::Regex(regex::Regex::new(
+                "^n",
+            )?)]);
+            let value = "id,name,score\n1,Ferris,0.5\n";
+            let fields = infer_csv_with_options(CsvInput::Value(value), &mut options)?;
+            assert_eq!(Some(1), fields[0].position);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn overrides() -> Result<(), Box<dyn Error>> {
+        let fields = infer_csv_with_options(
+            CsvInput::Value("zip,flag,count,n\n01234,Y,1,2\n98765,N,x,3\n"),
+            &mut InferOptions {
+                has_headers: true,
+                overrides: vec![
+                    (
+                        "zip".into(),
+                        Override::Replace(vec![DataType::Text(Text::default())]),
+                    ),
+                    (
+                        1.into(),
+                        Override::Extend(vec![DataType::Literal(Literal::new(vec![
+                            "Y".into(),
+                            "N".into(),
+                        ]))]),
+                    ),
+                    (
+                        "count".into(),
+                        Override::Pin(DataType::Integer(Integer::default())),
+                    ),
+                ],
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(1, fields[0].valid_types.len());
+        assert!(matches!(fields[1].valid_types[0], DataType::Literal(_)));
+        assert!(fields[2].pinned);
+        assert!(matches!(fields[2].valid_types[..], [DataType::Integer(_)]));
+        assert!(matches!(fields[3].valid_types[0], DataType::Integer(_)));
+        Ok(())
+    }
+}
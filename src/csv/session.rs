@@ -0,0 +1,627 @@
+use std::{borrow::Cow, error::Error, io::Read};
+
+use csv::{ByteRecord, Position, Reader, StringRecord, Trim};
+use csv_core::ReadRecordResult;
+
+use crate::{
+    csv::{
+        detect_headers,
+        frame::Frame,
+        header::{dedupe_names, missing_header_name},
+        is_null, normalize_header,
+        sampling::{keep, Sample, Sampler},
+        HeaderDetection, InferOptions, Override, SamplingStrategy, HEADER_SAMPLE_LEN,
+    },
+    field::Fields,
+    report::RejectedRecord,
+    DataType, Field, Validator,
+};
+
+/// An inference in progress, fed one record or one chunk of raw bytes at a
+/// time.
+///
+/// Unlike [`infer_csv_with_options`](crate::infer_csv_with_options), a session
+/// never needs the whole input up front, and [`InferSession::fields`] can be
+/// called at any point to snapshot what has been inferred so far.
+///
+/// The first record pushed is treated as the header row when
+/// [`InferOptions::has_headers`] is set. With
+/// [`InferOptions::detect_headers`], the first few records are held back
+/// until there are enough to decide.
+pub struct InferSession<'a> {
+    options: &'a InferOptions,
+    null_validator: DataType,
+    has_headers: bool,
+    part: bool,
+    frame: Frame,
+    pending: Option<Vec<StringRecord>>,
+    header: Option<HeaderDetection>,
+    fields: Option<Fields>,
+    records: u64,
+    rejected: Vec<RejectedRecord>,
+    sampler: Sampler,
+    reservoir: Vec<StringRecord>,
+    parser: csv_core::Reader,
+    offset: u64,
+    record_start: u64,
+    record_line: u64,
+    record_index: u64,
+    output: Vec<u8>,
+    output_len: usize,
+    ends: Vec<usize>,
+    ends_len: usize,
+}
+
+impl<'a> InferSession<'a> {
+    pub fn new(options: &'a InferOptions) -> Self {
+        InferSession {
+            options,
+            null_validator: options.null_validator.clone(),
+            has_headers: options.has_headers,
+            part: false,
+            frame: Frame::new(options),
+            pending: options.detect_headers.then(Vec::new),
+            header: None,
+            fields: None,
+            records: 0,
+            rejected: vec![],
+            sampler: Sampler::new(options.sampling),
+            reservoir: vec![],
+            parser: csv_core::ReaderBuilder::new()
+                .delimiter(options.delimiter)
+                .escape(options.escape)
+                .quote(options.quote)
+                .quoting(options.quoting)
+                .comment(options.comment)
+                .terminator(match options.terminator {
+                    csv::Terminator::Any(terminator) => csv_core::Terminator::Any(terminator),
+                    _ => csv_core::Terminator::CRLF,
+                })
+                .build(),
+            offset: 0,
+            record_start: 0,
+            record_line: 1,
+            record_index: 0,
+            output: vec![0; 1024],
+            output_len: 0,
+            ends: vec![0; 64],
+            ends_len: 0,
+        }
+    }
+
+    /// Number of data records considered so far, not counting the header row
+    /// or any records held in a sampling reservoir.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Whether the sampling strategy will consider no more records, so the
+    /// rest of the input need not be pushed.
+    pub fn done(&self) -> bool {
+        self.pending.is_none() && self.sampler.done()
+    }
+
+    /// Snapshot of the fields inferred so far. The session carries on
+    /// unaffected.
+    pub fn fields(&self) -> Fields {
+        if self.frame.is_deciding() {
+            // Nothing has got past the frame yet, so the records it holds are
+            // framed on the side as if the input ended here.
+            let mut session = InferSession::new(self.options);
+            session.frame = self.frame.clone();
+            session.frame.finish();
+            let _ = session.push_ready();
+            return session.fields();
+        }
+        match self.pending {
+            // Records still held back for header detection are considered on
+            // the side. Any error they cause is left for `finish` to report.
+            Some(ref pending) => {
+                let mut session = InferSession::new(self.options);
+                session.pending = Some(pending.clone());
+                let _ = session.settle_headers();
+                session.snapshot()
+            }
+            None => self.snapshot(),
+        }
+    }
+
+    fn snapshot(&self) -> Fields {
+        let mut fields = self.fields.clone().unwrap_or_default();
+        let mut records = self.records;
+        for record in &self.reservoir {
+            if consider(
+                self.options,
+                &mut self.null_validator.clone(),
+                &mut fields,
+                record,
+            )
+            .is_ok()
+            {
+                records += 1;
+            }
+        }
+        fields.report.header = self.header.clone();
+        fields.report.sampling = self.options.sampling;
+        fields.report.records = records;
+        fields.report.preamble = self.frame.preamble;
+        fields.report.footer = self.frame.footer();
+        fields.report.rejected = self.rejected.clone();
+        if !self.part {
+            fields.iter_mut().for_each(Field::settle);
+        }
+        fields
+    }
+
+    /// Skips a malformed record, failing instead when
+    /// [`InferOptions::max_errors`] is `None` or already reached.
+    pub(crate) fn reject(
+        &mut self,
+        position: Option<Position>,
+        error: Box<dyn Error>,
+    ) -> Result<(), Box<dyn Error>> {
+        reject(self.options, &mut self.rejected, position, error)
+    }
+
+    /// Sets the session up to infer one part of an input, to be merged with
+    /// the others. Every record pushed is considered, and types are not
+    /// dropped for rejecting too large a share of values, since that share is
+    /// only known once all the parts are merged.
+    pub(crate) fn into_part(mut self) -> Self {
+        self.sampler = Sampler::new(SamplingStrategy::All);
+        self.frame = Frame::none();
+        self.part = true;
+        self
+    }
+
+    /// Sets up the fields from a header row without considering it, taking it
+    /// as a header or not as told.
+    pub(crate) fn push_headers(&mut self, record: &StringRecord, has_headers: bool) {
+        self.has_headers = has_headers;
+        self.pending = None;
+        if self.fields.is_none() {
+            self.fields = Some(headers(
+                self.options,
+                has_headers,
+                &mut self.null_validator,
+                record,
+            ));
+        }
+    }
+
+    /// Decides on the header row from the records held back so far, then
+    /// considers them.
+    fn settle_headers(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(pending) = self.pending.take() {
+            let detection = detect_headers(&pending, self.options);
+            self.has_headers = detection.has_headers;
+            self.header = Some(detection);
+            for record in &pending {
+                self.push_table_record(record)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Considers a single parsed record.
+    pub fn push_record(&mut self, record: &StringRecord) -> Result<(), Box<dyn Error>> {
+        if self.frame.push(record.as_byte_record()) {
+            return self.push_table_record(record);
+        }
+        self.push_ready()
+    }
+
+    /// Pushes on the records the frame has made ready.
+    fn push_ready(&mut self) -> Result<(), Box<dyn Error>> {
+        while let Some(record) = self.frame.pop() {
+            self.push_table_byte_record(record)?;
+        }
+        Ok(())
+    }
+
+    /// Considers a record of the table itself.
+    fn push_table_record(&mut self, record: &StringRecord) -> Result<(), Box<dyn Error>> {
+        if let Some(ref mut pending) = self.pending {
+            pending.push(record.clone());
+            if pending.len() >= HEADER_SAMPLE_LEN {
+                self.settle_headers()?;
+            }
+            return Ok(());
+        }
+
+        let fields = match self.fields {
+            Some(ref mut fields) => fields,
+            None => {
+                let fields = self.fields.insert(headers(
+                    self.options,
+                    self.has_headers,
+                    &mut self.null_validator,
+                    record,
+                ));
+                if self.has_headers {
+                    return Ok(());
+                }
+                fields
+            }
+        };
+
+        match self.sampler.sample(record.position().map(Position::byte)) {
+            Sample::Consider => {
+                match consider(self.options, &mut self.null_validator, fields, record) {
+                    Ok(()) => self.records += 1,
+                    Err(err) => self.reject(record.position().cloned(), err)?,
+                }
+            }
+            Sample::Skip => (),
+            Sample::Keep(slot) => keep(&mut self.reservoir, slot, record.clone()),
+        }
+        Ok(())
+    }
+
+    /// Considers a single parsed record, rejecting it if it is not valid
+    /// UTF-8.
+    pub(crate) fn push_byte_record(&mut self, record: ByteRecord) -> Result<(), Box<dyn Error>> {
+        if self.frame.push(&record) {
+            return self.push_table_byte_record(record);
+        }
+        self.push_ready()
+    }
+
+    fn push_table_byte_record(&mut self, record: ByteRecord) -> Result<(), Box<dyn Error>> {
+        let position = record.position().cloned();
+        match StringRecord::from_byte_record(record) {
+            Ok(record) => self.push_table_record(&record),
+            Err(err) => self.reject(position, err.into()),
+        }
+    }
+
+    /// Parses a chunk of raw CSV bytes, considering every record it completes.
+    ///
+    /// Chunks may split records anywhere; the remainder is kept until the next
+    /// chunk or [`InferSession::finish`].
+    pub fn push_bytes(&mut self, mut chunk: &[u8]) -> Result<(), Box<dyn Error>> {
+        while !chunk.is_empty() {
+            if self.read_record(&mut chunk)? == ReadRecordResult::End {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Considers any record left unterminated by the last chunk, along with
+    /// any sampling reservoir, and returns the inferred fields.
+    pub fn finish(mut self) -> Result<Fields, Box<dyn Error>> {
+        let mut eof: &[u8] = &[];
+        while self.read_record(&mut eof)? != ReadRecordResult::End {}
+        self.frame.finish();
+        self.push_ready()?;
+        self.settle_headers()?;
+        for record in std::mem::take(&mut self.reservoir) {
+            let Some(ref mut fields) = self.fields else {
+                break;
+            };
+            match consider(self.options, &mut self.null_validator, fields, &record) {
+                Ok(()) => self.records += 1,
+                Err(err) => self.reject(record.position().cloned(), err)?,
+            }
+        }
+        Ok(self.snapshot())
+    }
+
+    fn read_record(&mut self, input: &mut &[u8]) -> Result<ReadRecordResult, Box<dyn Error>> {
+        let (result, read, written, ended) = self.parser.read_record(
+            input,
+            &mut self.output[self.output_len..],
+            &mut self.ends[self.ends_len..],
+        );
+        *input = &input[read..];
+        self.offset += read as u64;
+        self.output_len += written;
+        self.ends_len += ended;
+        match result {
+            ReadRecordResult::OutputFull => self.output.resize(self.output.len() * 2, 0),
+            ReadRecordResult::OutputEndsFull => self.ends.resize(self.ends.len() * 2, 0),
+            ReadRecordResult::Record => {
+                let mut record = ByteRecord::with_capacity(self.output_len, self.ends_len);
+                let mut start = 0;
+                for &end in &self.ends[..self.ends_len] {
+                    record.push_field(&self.output[start..end]);
+                    start = end;
+                }
+                let mut position = Position::new();
+                position.set_byte(self.record_start);
+                position.set_line(self.record_line);
+                position.set_record(self.record_index);
+                record.set_position(Some(position));
+                self.record_start = self.offset;
+                self.record_line = self.parser.line();
+                self.record_index += 1;
+                self.output_len = 0;
+                self.ends_len = 0;
+                self.push_byte_record(record)?;
+            }
+            ReadRecordResult::InputEmpty | ReadRecordResult::End => (),
+        }
+        Ok(result)
+    }
+}
+
+/// Infers the fields of the records of `reader` as they are read, for options
+/// that need none of the sampling, header detection, framing or progress
+/// reports of an [`InferSession`].
+pub(crate) fn infer_records<R: Read>(
+    options: &InferOptions,
+    reader: &mut Reader<R>,
+) -> Result<Fields, Box<dyn Error>> {
+    let mut null_validator = options.null_validator.clone();
+    let mut fields: Option<Fields> = None;
+    let mut rejected = vec![];
+    let mut records = 0;
+    let mut record = StringRecord::new();
+    loop {
+        match reader.read_record(&mut record) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(err) if err.is_io_error() => return Err(err.into()),
+            Err(err) => {
+                reject(options, &mut rejected, err.position().cloned(), err.into())?;
+                continue;
+            }
+        }
+        let fields = match fields {
+            Some(ref mut fields) => fields,
+            None => {
+                let fields = fields.insert(headers(
+                    options,
+                    options.has_headers,
+                    &mut null_validator,
+                    &record,
+                ));
+                if options.has_headers {
+                    continue;
+                }
+                fields
+            }
+        };
+        match consider(options, &mut null_validator, fields, &record) {
+            Ok(()) => records += 1,
+            Err(err) => reject(options, &mut rejected, record.position().cloned(), err)?,
+        }
+    }
+
+    let mut fields = fields.unwrap_or_default();
+    fields.report.sampling = options.sampling;
+    fields.report.records = records;
+    fields.report.rejected = rejected;
+    fields.iter_mut().for_each(Field::settle);
+    Ok(fields)
+}
+
+/// Adds a malformed record to `rejected`, failing instead when
+/// [`InferOptions::max_errors`] is `None` or already reached.
+pub(crate) fn reject(
+    options: &InferOptions,
+    rejected: &mut Vec<RejectedRecord>,
+    position: Option<Position>,
+    error: Box<dyn Error>,
+) -> Result<(), Box<dyn Error>> {
+    match options.max_errors {
+        None => Err(error),
+        Some(max) if rejected.len() >= max => Err(too_many_errors(max, error)),
+        Some(_) => {
+            rejected.push(RejectedRecord {
+                position,
+                error: error.to_string(),
+            });
+            Ok(())
+        }
+    }
+}
+
+/// The error given once more than `max` malformed records have been found.
+pub(crate) fn too_many_errors(max: usize, last: Box<dyn Error>) -> Box<dyn Error> {
+    format!("more than {max} malformed records, the last being: {last}").into()
+}
+
+/// Considers one data record against the fields.
+fn consider(
+    options: &InferOptions,
+    null_validator: &mut DataType,
+    fields: &mut Fields,
+    record: &StringRecord,
+) -> Result<(), Box<dyn Error>> {
+    if !options.flexible && record.len() != fields.report.columns {
+        return Err(format!(
+            "found record with {} fields, but the previous record has {} fields",
+            record.len(),
+            fields.report.columns
+        )
+        .into());
+    }
+
+    let records = fields.report.row_lengths.values().sum();
+    *fields.report.row_lengths.entry(record.len()).or_default() += 1;
+
+    if options.flexible && options.columns.is_none() && record.len() > fields.len() {
+        for position in fields.len()..record.len() {
+            let name = options
+                .name_missing_headers
+                .then(|| missing_header_name(position));
+            let mut field = Field {
+                nullable: true,
+                ..new_field(options, position, None, name)
+            };
+            // Every record before this one was too short to reach it.
+            field.consider_missing(records);
+            fields.push(field)
+        }
+        if options.dedupe_headers {
+            dedupe_names(fields);
+        }
+    }
+
+    let trim = matches!(options.trim, Trim::Fields | Trim::All);
+    let width = fields.report.columns;
+    for (index, field) in fields.iter_mut().enumerate() {
+        let position = field.position.unwrap_or(index);
+        let Some(value) = record.get(position) else {
+            field.consider_missing(1);
+            continue;
+        };
+        if position >= width {
+            field.extra += 1;
+        }
+        let value = if trim { value.trim() } else { value };
+        if is_null(options, null_validator, value) {
+            field.consider_null(value)
+        } else {
+            field.consider_at(value, record.position())
+        }
+    }
+    Ok(())
+}
+
+/// A field for the column at `position`, with the candidate types and
+/// tolerances given by `options`.
+pub(crate) fn new_field(
+    options: &InferOptions,
+    position: usize,
+    header: Option<&str>,
+    name: Option<String>,
+) -> Field {
+    let mut data_types = options.data_types.clone();
+    let mut pinned = false;
+    for (selector, column_override) in &options.overrides {
+        if selector.matches(position, header) {
+            match column_override {
+                Override::Replace(replacement) => data_types = replacement.clone(),
+                Override::Pin(data_type) => {
+                    data_types = vec![data_type.clone()];
+                    pinned = true;
+                }
+                Override::Extend(extra) => {
+                    data_types.splice(0..0, extra.iter().cloned());
+                }
+            }
+        }
+    }
+    Field {
+        position: Some(position),
+        pinned,
+        ..Field::new(name, data_types).with_tolerance(|data_type| options.tolerance_for(data_type))
+    }
+}
+
+fn headers(
+    options: &InferOptions,
+    has_headers: bool,
+    null_validator: &mut DataType,
+    record: &StringRecord,
+) -> Fields {
+    let original = record;
+    let record = if matches!(options.trim, Trim::Headers | Trim::All) {
+        let mut record = record.clone();
+        record.trim();
+        Cow::Owned(record)
+    } else {
+        Cow::Borrowed(record)
+    };
+
+    let mut fields = Fields::new(
+        record
+            .iter()
+            .enumerate()
+            .filter(|&(position, value)| {
+                options.columns.as_ref().is_none_or(|selectors| {
+                    selectors
+                        .iter()
+                        .any(|selector| selector.matches(position, has_headers.then_some(value)))
+                })
+            })
+            .map(|(position, value)| {
+                let header = has_headers.then_some(value);
+                let name = header
+                    .filter(|header| !null_validator.validate(header))
+                    .map(|header| normalize_header(header, options.header_case))
+                    .filter(|name| !name.is_empty())
+                    .or_else(|| {
+                        options
+                            .name_missing_headers
+                            .then(|| missing_header_name(position))
+                    });
+                Field {
+                    header: has_headers.then(|| original[position].to_string()),
+                    ..new_field(options, position, header, name)
+                }
+            })
+            .collect(),
+    );
+    if options.dedupe_headers {
+        dedupe_names(&mut fields);
+    }
+    fields.report.columns = record.len();
+    fields
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, error::Error};
+
+    use crate::{
+        csv::InferSession, infer_csv_parallel, infer_csv_with_options, CsvInput, DataType,
+        InferOptions,
+    };
+
+    #[test]
+    fn chunks() -> Result<(), Box<dyn Error>> {
+        let options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let mut session = InferSession::new(&options);
+        session.push_bytes(b"id,na")?;
+        session.push_bytes(b"me\n1,Fer")?;
+        assert_eq!(0, session.records());
+
+        session.push_bytes(b"ris\n2")?;
+        let fields = session.fields();
+        assert_eq!(1, session.records());
+        assert_eq!(Some("name".into()), fields[1].name);
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+
+        session.push_bytes(b".5,Corro")?;
+        let fields = session.finish()?;
+        assert!(matches!(fields[0].valid_types[0], DataType::Float(_)));
+        Ok(())
+    }
+
+    #[test]
+    fn ragged() -> Result<(), Box<dyn Error>> {
+        let value = "a,b,c\n1,2,3\n4,5\n6,7,8,9\n";
+        let mut options = InferOptions {
+            has_headers: true,
+            flexible: true,
+            ..InferOptions::default()
+        };
+        let fields = infer_csv_with_options(CsvInput::Value(value), &mut options)?;
+        let parallel = infer_csv_parallel(CsvInput::Value(value), &mut options, 2)?;
+        assert_eq!(
+            fields.iter().map(|field| field.extra).collect::<Vec<_>>(),
+            parallel.iter().map(|field| field.extra).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(false, 0, 0), (false, 0, 0), (true, 1, 0), (true, 2, 1)],
+            fields
+                .iter()
+                .map(|field| (field.nullable, field.missing, field.extra))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            BTreeMap::from([(2, 1), (3, 1), (4, 1)]),
+            fields.report.row_lengths
+        );
+        assert_eq!(1, fields.report.short_rows());
+        assert_eq!(1, fields.report.long_rows());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use csv::{ByteRecord, ReaderBuilder, StringRecord, Terminator};
+
+use crate::csv::{detect_headers, InferOptions, HEADER_SAMPLE_LEN};
+
+/// How many bytes [`infer_csv_auto`](crate::infer_csv_auto) samples from the
+/// start of its input to sniff the dialect.
+pub const SAMPLE_LEN: u64 = 64 * 1024;
+
+const DELIMITERS: [u8; 5] = [b',', b';', b'\t', b'|', b':'];
+const QUOTES: [u8; 2] = [b'"', b'\''];
+
+/// A CSV dialect proposed by [`sniff`].
+#[derive(Debug, Clone, Copy)]
+pub struct Dialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub quoting: bool,
+    pub terminator: Terminator,
+    pub has_headers: bool,
+    /// From 0 to 1, the share of sampled records with the most common number
+    /// of fields, discounted when another delimiter fits nearly as well.
+    pub confidence: f64,
+}
+
+impl Dialect {
+    /// Overwrites the dialect fields of `options`, leaving the rest alone.
+    pub fn apply(&self, options: &mut InferOptions) {
+        options.delimiter = self.delimiter;
+        options.quote = self.quote;
+        options.quoting = self.quoting;
+        options.terminator = self.terminator;
+        options.has_headers = self.has_headers;
+    }
+
+    /// Default [`InferOptions`] with this dialect applied.
+    pub fn options(&self) -> InferOptions {
+        let mut options = InferOptions::default();
+        self.apply(&mut options);
+        options
+    }
+}
+
+struct Candidate {
+    delimiter: u8,
+    quote: u8,
+    records: Vec<ByteRecord>,
+    consistency: f64,
+}
+
+impl Candidate {
+    fn new(sample: &[u8], delimiter: u8, quote: u8, terminator: Terminator) -> Self {
+        let records: Vec<_> = ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(delimiter)
+            .quote(quote)
+            .terminator(terminator)
+            .from_reader(sample)
+            .into_byte_records()
+            .filter_map(Result::ok)
+            .collect();
+
+        let mut widths = HashMap::new();
+        for record in &records {
+            *widths.entry(record.len()).or_insert(0) += 1;
+        }
+        let (width, count) = widths
+            .into_iter()
+            .max_by_key(|&(width, count)| (count, width))
+            .unwrap_or_default();
+
+        Candidate {
+            delimiter,
+            quote,
+            consistency: if width > 1 {
+                count as f64 / records.len() as f64
+            } else {
+                0.0
+            },
+            records,
+        }
+    }
+}
+
+/// Proposes the dialect of a sample taken from the start of some CSV input.
+///
+/// Every combination of common delimiters and quotes is tried, and the one
+/// splitting the most records into the same number of fields wins.
+pub fn sniff(sample: &[u8]) -> Dialect {
+    // The sample may well end part way through a record.
+    let sample = match sample
+        .iter()
+        .rposition(|&byte| byte == b'\n' || byte == b'\r')
+    {
+        Some(end) if end + 1 < sample.len() => &sample[..=end],
+        _ => sample,
+    };
+    let terminator =
+        if !sample.contains(&b'\n') && !sample.contains(&b'\r') && sample.contains(&0x1e) {
+            Terminator::Any(0x1e)
+        } else {
+            Terminator::CRLF
+        };
+
+    let mut best: Option<Candidate> = None;
+    let mut runner_up = 0.0_f64;
+    for delimiter in DELIMITERS {
+        let candidate = QUOTES
+            .into_iter()
+            .map(|quote| Candidate::new(sample, delimiter, quote, terminator))
+            .reduce(|a, b| if b.consistency > a.consistency { b } else { a })
+            .unwrap();
+        match best {
+            Some(ref current) if candidate.consistency <= current.consistency => {
+                runner_up = runner_up.max(candidate.consistency)
+            }
+            _ => {
+                runner_up = runner_up.max(best.as_ref().map_or(0.0, |best| best.consistency));
+                best = Some(candidate);
+            }
+        }
+    }
+    let best = best.unwrap();
+
+    Dialect {
+        delimiter: best.delimiter,
+        quote: best.quote,
+        quoting: true,
+        terminator,
+        has_headers: detect_headers(
+            &best
+                .records
+                .into_iter()
+                .take(HEADER_SAMPLE_LEN)
+                .filter_map(|record| StringRecord::from_byte_record(record).ok())
+                .collect::<Vec<_>>(),
+            &InferOptions::default(),
+        )
+        .has_headers,
+        confidence: best.consistency * (1.0 - runner_up / 2.0),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{infer_csv_auto, sniff, CsvInput, DataType, InferOptions};
+
+    #[test]
+    fn delimiter() {
+        let dialect = sniff(b"name;score\nFerris;0.5\nCorro;1.5\n");
+        assert_eq!(b';', dialect.delimiter);
+        assert!(dialect.has_headers);
+
+        let dialect = sniff(b"'a\tb'\t1\t2\n'c'\t2\t3\nd\t5\t");
+        assert_eq!(b'\t', dialect.delimiter);
+        assert_eq!(b'\'', dialect.quote);
+        assert!(!dialect.has_headers);
+        assert_eq!(1.0, dialect.confidence);
+    }
+
+    #[test]
+    fn auto() -> Result<(), Box<dyn Error>> {
+        let mut options = InferOptions::default();
+        let fields = infer_csv_auto(
+            CsvInput::Value("name|loc\nFerris|42\nCorro|7\n"),
+            &mut options,
+        )?;
+        assert_eq!(b'|', options.delimiter);
+        assert_eq!(Some("loc".into()), fields[1].name);
+        assert!(matches!(fields[1].valid_types[0], DataType::Integer(_)));
+        Ok(())
+    }
+}
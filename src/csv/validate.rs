@@ -0,0 +1,474 @@
+use std::{error::Error, fmt};
+
+use csv::{Position, StringRecord, Trim};
+use tabled::{builder::Builder, settings::Style};
+
+use crate::{
+    csv::{
+        csv_reader,
+        frame::{frame_records, Frame},
+        is_null, normalize_header, CsvInput, InferOptions,
+    },
+    field::Fields,
+    DataType, Unique, Validator,
+};
+
+#[derive(Default)]
+pub struct ValidateOptions {
+    /// How to read the input. The data types and tolerances are ignored, as
+    /// the schema gives those.
+    pub csv: InferOptions,
+    /// Stops validating once this many violations have been found.
+    pub max_violations: Option<usize>,
+}
+
+/// What a [`Violation`] was expected to be instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expected {
+    /// A value of the [named](DataType::name) type.
+    Type(&'static str),
+    /// Any value but a null, as the column is not nullable.
+    NotNull,
+    /// A record with this many fields.
+    Fields(usize),
+    /// A column in the header for the field, which the header lacks. The
+    /// value is the name of the field.
+    Column,
+    /// No column of this name, as the schema has no field for it. The value
+    /// is the name.
+    NoColumn,
+    /// A record that can be read, rather than one that is malformed or not
+    /// valid UTF-8. The value is the error.
+    Record,
+}
+
+/// A value, or a whole record, that does not fit the schema.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    /// Where the record holding the value starts.
+    pub position: Option<Position>,
+    /// Index of the field in the schema the value was checked against, or
+    /// `None` for a violation by a whole record, or by a column the schema
+    /// lacks.
+    pub column: Option<usize>,
+    pub value: String,
+    pub expected: Expected,
+}
+
+/// The outcome of [`validate_csv`].
+#[derive(Default, Debug, Clone)]
+pub struct ValidationReport {
+    /// Every violation found, in input order.
+    pub violations: Vec<Violation>,
+    /// Number of data records checked.
+    pub records: u64,
+    /// Number of those records with at least one violation.
+    pub invalid_records: u64,
+    /// Name of each column of the schema, where it has one.
+    pub columns: Vec<Option<String>>,
+    /// Number of violations found in each column of the schema.
+    pub column_violations: Vec<u64>,
+    /// Whether validation stopped early at
+    /// [`ValidateOptions::max_violations`], leaving the rest unchecked.
+    pub truncated: bool,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    fn is_full(&self, max_violations: Option<usize>) -> bool {
+        max_violations.is_some_and(|max| self.violations.len() >= max)
+    }
+
+    /// Records a violation, unless there are already as many as allowed.
+    fn push(&mut self, max_violations: Option<usize>, violation: Violation) {
+        if self.is_full(max_violations) {
+            self.truncated = true;
+            return;
+        }
+        if let Some(column) = violation.column {
+            self.column_violations[column] += 1;
+        }
+        self.violations.push(violation);
+    }
+}
+
+/// Checks every record of `input` against `schema`, as inferred earlier by
+/// [`infer_csv_with_options`](crate::infer_csv_with_options) for instance.
+///
+/// Each value must be null, if the field is nullable, or else valid for the
+/// field's [most specific type](crate::Field::data_type). Under
+/// [`InferOptions::flexible`], a value missing from a short record counts as a
+/// null. A record that cannot be read is a violation of its own. Records are
+/// read one at a time, so the input is never held in memory.
+///
+/// With [`InferOptions::has_headers`], fields are matched to the columns of
+/// the input by name, so that columns may come in any order. Fields missing
+/// from the header, and columns of the header the schema lacks, are
+/// violations. Fields without a name, and every field when the input has no
+/// header, are matched by position.
+pub fn validate_csv(
+    input: CsvInput,
+    schema: &Fields,
+    options: &ValidateOptions,
+) -> Result<ValidationReport, Box<dyn Error>> {
+    let csv = &options.csv;
+    let max = options.max_violations;
+    let mut null_validator = csv.null_validator.clone();
+    // Unique values are only checked among the new data.
+    let mut validators: Vec<_> = schema
+        .iter()
+        .map(|field| {
+            field.data_type().map(|data_type| match data_type {
+                DataType::Unique(_) => DataType::Unique(Unique::default()),
+                data_type => data_type.clone(),
+            })
+        })
+        .collect();
+    // The column of the input each field is read from, if any.
+    let mut columns: Vec<_> = schema
+        .iter()
+        .enumerate()
+        .map(|(index, field)| Some(field.position.unwrap_or(index)))
+        .collect();
+    // Only some columns of the input may be in the schema.
+    let mut width = schema.report.columns.max(schema.len());
+    let mut report = ValidationReport {
+        columns: schema.iter().map(|field| field.name.clone()).collect(),
+        column_violations: vec![0; schema.len()],
+        ..ValidationReport::default()
+    };
+
+    let (mut reader, _) = csv_reader(input, csv)?;
+    let mut frame = Frame::new(csv);
+    for (i, record) in frame_records(&mut frame, reader.byte_records()).enumerate() {
+        if report.is_full(max) {
+            report.truncated = true;
+            break;
+        }
+        let record = match record.map(StringRecord::from_byte_record) {
+            Ok(Ok(record)) => record,
+            Err(err) if i > 0 && !matches!(err.kind(), csv::ErrorKind::Io(_)) => {
+                report.records += 1;
+                report.invalid_records += 1;
+                report.push(max, malformed(err.position().cloned(), err.to_string()));
+                continue;
+            }
+            Ok(Err(err)) if i > 0 => {
+                report.records += 1;
+                report.invalid_records += 1;
+                let error = err.utf8_error().to_string();
+                let position = err.into_byte_record().position().cloned();
+                report.push(max, malformed(position, error));
+                continue;
+            }
+            Err(err) => return Err(err.into()),
+            Ok(Err(err)) => return Err(err.into()),
+        };
+        if i == 0 && csv.has_headers {
+            width = record.len();
+            match_header(schema, csv, &record, &mut columns, &mut report, max);
+            continue;
+        }
+        report.records += 1;
+
+        let violations = report.violations.len();
+        let mut violation = |column: Option<usize>, value: &str, expected| {
+            report.push(
+                max,
+                Violation {
+                    position: record.position().cloned(),
+                    column,
+                    value: value.to_string(),
+                    expected,
+                },
+            )
+        };
+
+        if !csv.flexible && record.len() != width {
+            violation(None, "", Expected::Fields(width));
+        } else {
+            let trim = matches!(csv.trim, Trim::Fields | Trim::All);
+            let fields = schema.iter().zip(validators.iter_mut()).zip(&columns);
+            for (column, ((field, validator), index)) in fields.enumerate() {
+                // A field missing from the header is reported there.
+                let Some(index) = *index else {
+                    continue;
+                };
+                let Some(value) = record.get(index) else {
+                    // A record too short to reach the column is missing the
+                    // value, which counts as a null as it does in inference.
+                    if !field.nullable {
+                        violation(Some(column), "", Expected::NotNull);
+                    }
+                    continue;
+                };
+                let value = if trim { value.trim() } else { value };
+                if is_null(csv, &mut null_validator, value) {
+                    if !field.nullable {
+                        violation(Some(column), value, Expected::NotNull);
+                    }
+                } else if let Some(validator) = validator {
+                    if !validator.validate(value) {
+                        violation(Some(column), value, Expected::Type(validator.name()));
+                    }
+                }
+            }
+        }
+
+        if report.violations.len() > violations {
+            report.invalid_records += 1;
+        }
+    }
+    Ok(report)
+}
+
+fn malformed(position: Option<Position>, error: String) -> Violation {
+    Violation {
+        position,
+        column: None,
+        value: error,
+        expected: Expected::Record,
+    }
+}
+
+/// Finds the column of `header` for each named field of `schema`, reporting
+/// the fields it lacks and, when the schema has every column of the input,
+/// the columns it has that the schema lacks.
+fn match_header(
+    schema: &Fields,
+    csv: &InferOptions,
+    header: &StringRecord,
+    columns: &mut [Option<usize>],
+    report: &mut ValidationReport,
+    max: Option<usize>,
+) {
+    let trim = matches!(csv.trim, Trim::Headers | Trim::All);
+    let names: Vec<_> = header
+        .iter()
+        .map(|name| if trim { name.trim() } else { name })
+        .collect();
+    let mut matched = vec![false; names.len()];
+    let violation = |column, value: &str, expected| Violation {
+        position: header.position().cloned(),
+        column,
+        value: value.to_string(),
+        expected,
+    };
+
+    for (column, field) in schema.iter().enumerate() {
+        let Some(ref name) = field.name else {
+            continue;
+        };
+        let found = names.iter().enumerate().position(|(index, header)| {
+            !matched[index]
+                && (field.header.as_deref() == Some(*header)
+                    || normalize_header(header, csv.header_case) == *name)
+        });
+        match found {
+            Some(index) => matched[index] = true,
+            None => report.push(max, violation(Some(column), name, Expected::Column)),
+        }
+        columns[column] = found;
+    }
+    // Fields without a name keep to their position.
+    for index in columns.iter().flatten() {
+        if let Some(matched) = matched.get_mut(*index) {
+            *matched = true;
+        }
+    }
+
+    if schema.report.columns <= schema.len() {
+        for (name, _) in names.iter().zip(&matched).filter(|(_, &matched)| !matched) {
+            report.push(max, violation(None, name, Expected::NoColumn));
+        }
+    }
+}
+
+/// Summarises the violations found in each column.
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} of {} records invalid{}",
+            self.invalid_records,
+            self.records,
+            if self.truncated {
+                ", stopped early"
+            } else {
+                ""
+            }
+        )?;
+        let mut builder = Builder::default();
+        builder.set_header(["column", "violations"]);
+        for (column, violations) in self.column_violations.iter().enumerate() {
+            let name = match self.columns.get(column) {
+                Some(Some(name)) => name.clone(),
+                _ => column.to_string(),
+            };
+            builder.push_record([name, violations.to_string()]);
+        }
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        write!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{
+        csv::{validate_csv, Encoding, Expected, ValidateOptions},
+        field::Fields,
+        infer_csv_with_options, CsvInput, DataType, Field, InferOptions, Integer, Text,
+    };
+
+    #[test]
+    fn violations() -> Result<(), Box<dyn Error>> {
+        let mut options = ValidateOptions {
+            csv: InferOptions {
+                has_headers: true,
+                ..InferOptions::default()
+            },
+            ..ValidateOptions::default()
+        };
+        let schema =
+            infer_csv_with_options(CsvInput::Value("id,score\n1,0.5\n2,\n"), &mut options.csv)?;
+
+        let input = "id,score\n3,1.5\n,2\nx,y\n4\n5,\n";
+        let report = validate_csv(CsvInput::Value(input), &schema, &options)?;
+        assert_eq!(5, report.records);
+        assert_eq!(3, report.invalid_records);
+        assert_eq!(vec![2, 1], report.column_violations);
+        let expected: Vec<_> = report
+            .violations
+            .iter()
+            .map(|violation| (violation.column, violation.expected.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (Some(0), Expected::NotNull),
+                (Some(0), Expected::Type("Integer")),
+                (Some(1), Expected::Type("Float")),
+                (None, Expected::Fields(2)),
+            ],
+            expected
+        );
+        assert_eq!(4, report.violations[1].position.as_ref().unwrap().line());
+
+        options.max_violations = Some(2);
+        let report = validate_csv(CsvInput::Value(input), &schema, &options)?;
+        assert!(report.truncated);
+        assert_eq!(2, report.violations.len());
+        assert_eq!(3, report.records);
+        Ok(())
+    }
+
+    #[test]
+    fn missing() -> Result<(), Box<dyn Error>> {
+        let mut options = ValidateOptions {
+            csv: InferOptions {
+                has_headers: true,
+                flexible: true,
+                ..InferOptions::default()
+            },
+            ..ValidateOptions::default()
+        };
+        let schema = infer_csv_with_options(CsvInput::Value("a,b\n1,2\n3,4\n"), &mut options.csv)?;
+        let report = validate_csv(CsvInput::Value("a,b\n1\n"), &schema, &options)?;
+        assert_eq!(1, report.violations.len());
+        assert_eq!(Some(1), report.violations[0].column);
+        assert_eq!(Expected::NotNull, report.violations[0].expected);
+
+        let schema = infer_csv_with_options(CsvInput::Value("a,b\n1,2\n3\n"), &mut options.csv)?;
+        let report = validate_csv(CsvInput::Value("a,b\n1\n"), &schema, &options)?;
+        assert!(report.is_valid());
+        Ok(())
+    }
+
+    #[test]
+    fn hand_built() -> Result<(), Box<dyn Error>> {
+        let schema = Fields::new(vec![
+            Field::new(Some("name".into()), vec![DataType::Text(Text::default())]),
+            Field::new(
+                Some("id".into()),
+                vec![DataType::Integer(Integer::default())],
+            ),
+        ]);
+        let input = CsvInput::Value("x,1\ny,z\n");
+        let report = validate_csv(input, &schema, &ValidateOptions::default())?;
+        assert_eq!(1, report.violations.len());
+        assert_eq!(Some(1), report.violations[0].column);
+        assert_eq!("z", report.violations[0].value);
+        Ok(())
+    }
+
+    #[test]
+    fn header() -> Result<(), Box<dyn Error>> {
+        let mut options = ValidateOptions {
+            csv: InferOptions {
+                has_headers: true,
+                ..InferOptions::default()
+            },
+            ..ValidateOptions::default()
+        };
+        let schema =
+            infer_csv_with_options(CsvInput::Value("id,name\n1,Ferris\n"), &mut options.csv)?;
+
+        let report = validate_csv(CsvInput::Value("name,id\nCorro,2\n"), &schema, &options)?;
+        assert!(report.is_valid());
+        let report = validate_csv(CsvInput::Value("name,id\n2,Corro\n"), &schema, &options)?;
+        assert_eq!(1, report.violations.len());
+        assert_eq!(Some(0), report.violations[0].column);
+        assert_eq!("Corro", report.violations[0].value);
+
+        let report = validate_csv(CsvInput::Value("id,age\n2,3\n"), &schema, &options)?;
+        let expected: Vec<_> = report
+            .violations
+            .iter()
+            .map(|violation| {
+                (
+                    violation.column,
+                    violation.value.as_str(),
+                    &violation.expected,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                (Some(1), "name", &Expected::Column),
+                (None, "age", &Expected::NoColumn),
+            ],
+            expected
+        );
+        assert!(report.to_string().contains("name"));
+        Ok(())
+    }
+
+    #[test]
+    fn malformed() -> Result<(), Box<dyn Error>> {
+        let options = ValidateOptions {
+            csv: InferOptions {
+                has_headers: true,
+                encoding: Some(Encoding::Utf8),
+                ..InferOptions::default()
+            },
+            ..ValidateOptions::default()
+        };
+        let schema = Fields::new(vec![Field::new(
+            Some("id".into()),
+            vec![DataType::Integer(Integer::default())],
+        )]);
+        let input = CsvInput::Bytes(b"id\n1\n\xff\nx\n");
+        let report = validate_csv(input, &schema, &options)?;
+        assert_eq!(3, report.records);
+        assert_eq!(2, report.invalid_records);
+        assert_eq!(Expected::Record, report.violations[0].expected);
+        assert_eq!(3, report.violations[0].position.as_ref().unwrap().line());
+        assert_eq!(Expected::Type("Integer"), report.violations[1].expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use std::fmt;
+
+use tabled::{builder::Builder, settings::Style};
+
+#[cfg(feature = "time")]
+use crate::validators::time::DateTimeFormat;
+use crate::{field::Fields, DataType, Field};
+
+/// How the schema changed between two [`Fields`], column by column. See
+/// [`Fields::diff`].
+#[derive(Default, Debug, Clone)]
+pub struct SchemaDiff {
+    /// Columns that changed in any way, in the order of the old schema
+    /// followed by any added columns.
+    pub columns: Vec<ColumnDiff>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColumnDiff {
+    /// Index of the column in the old schema, if it was there.
+    pub old: Option<usize>,
+    /// Index of the column in the new schema, if it is there.
+    pub new: Option<usize>,
+    /// The name of the column, taken from the new schema where it has one.
+    pub name: Option<String>,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added,
+    Removed,
+    /// A different name in the same position.
+    Renamed {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// Types no longer valid, by [name](DataType::name).
+    Narrowed(Vec<&'static str>),
+    /// Types newly valid, by [name](DataType::name).
+    Widened(Vec<&'static str>),
+    Nullable {
+        from: bool,
+        to: bool,
+    },
+    IntegerRange {
+        from: (Option<i128>, Option<i128>),
+        to: (Option<i128>, Option<i128>),
+    },
+    FloatRange {
+        from: (Option<f64>, Option<f64>),
+        to: (Option<f64>, Option<f64>),
+    },
+    /// The formats of a date or time type.
+    Formats {
+        data_type: &'static str,
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+impl Fields {
+    /// Compares these fields with `new` ones, inferred from a later version of
+    /// the same input.
+    ///
+    /// Columns are paired by name, and then by position when neither has a
+    /// match, in which case a differing name counts as a rename. A column
+    /// that only held nulls on one side has no known type there, so its types
+    /// are not compared.
+    pub fn diff(&self, new: &Fields) -> SchemaDiff {
+        let mut pairs: Vec<Option<usize>> = self
+            .iter()
+            .map(|field| {
+                field.name.as_ref().and_then(|name| {
+                    new.iter()
+                        .position(|other| other.name.as_ref() == Some(name))
+                })
+            })
+            .collect();
+        for (i, pair) in pairs.clone().iter().enumerate() {
+            if pair.is_none() && i < new.len() && !pairs.contains(&Some(i)) {
+                pairs[i] = Some(i);
+            }
+        }
+
+        let mut columns = vec![];
+        for (i, (field, pair)) in self.iter().zip(&pairs).enumerate() {
+            let (changes, name) = match pair {
+                Some(j) => (changes(field, &new[*j]), new[*j].name.clone()),
+                None => (vec![Change::Removed], field.name.clone()),
+            };
+            if !changes.is_empty() {
+                columns.push(ColumnDiff {
+                    old: Some(i),
+                    new: *pair,
+                    name: name.or(field.name.clone()),
+                    changes,
+                })
+            }
+        }
+        for (j, field) in new.iter().enumerate() {
+            if !pairs.contains(&Some(j)) {
+                columns.push(ColumnDiff {
+                    old: None,
+                    new: Some(j),
+                    name: field.name.clone(),
+                    changes: vec![Change::Added],
+                })
+            }
+        }
+        SchemaDiff { columns }
+    }
+}
+
+fn changes(old: &Field, new: &Field) -> Vec<Change> {
+    let mut changes = vec![];
+    if old.name != new.name {
+        changes.push(Change::Renamed {
+            from: old.name.clone(),
+            to: new.name.clone(),
+        });
+    }
+
+    // A column that only held nulls has every type, for want of values.
+    let typed = !is_untyped(old) && !is_untyped(new);
+    let names = |field: &Field| {
+        field
+            .valid_types
+            .iter()
+            .map(DataType::name)
+            .collect::<Vec<_>>()
+    };
+    let (old_names, new_names) = (names(old), names(new));
+    let narrowed: Vec<_> = old_names
+        .iter()
+        .filter(|name| !new_names.contains(name))
+        .copied()
+        .collect();
+    if typed && !narrowed.is_empty() {
+        changes.push(Change::Narrowed(narrowed));
+    }
+    let widened: Vec<_> = new_names
+        .iter()
+        .filter(|name| !old_names.contains(name))
+        .copied()
+        .collect();
+    if typed && !widened.is_empty() {
+        changes.push(Change::Widened(widened));
+    }
+
+    if old.nullable != new.nullable {
+        changes.push(Change::Nullable {
+            from: old.nullable,
+            to: new.nullable,
+        });
+    }
+    if !typed {
+        return changes;
+    }
+
+    for data_type in &old.valid_types {
+        let Some(other) = new
+            .valid_types
+            .iter()
+            .find(|other| other.name() == data_type.name())
+        else {
+            continue;
+        };
+        match (data_type, other) {
+            (DataType::Integer(a), DataType::Integer(b))
+                if (a.min_value, a.max_value) != (b.min_value, b.max_value) =>
+            {
+                changes.push(Change::IntegerRange {
+                    from: (a.min_value, a.max_value),
+                    to: (b.min_value, b.max_value),
+                })
+            }
+            (DataType::Float(a), DataType::Float(b))
+                if (a.min_value, a.max_value) != (b.min_value, b.max_value) =>
+            {
+                changes.push(Change::FloatRange {
+                    from: (a.min_value, a.max_value),
+                    to: (b.min_value, b.max_value),
+                })
+            }
+            _ => {
+                if let (Some(from), Some(to)) = (formats(data_type), formats(other)) {
+                    if from != to {
+                        changes.push(Change::Formats {
+                            data_type: data_type.name(),
+                            from,
+                            to,
+                        })
+                    }
+                }
+            }
+        }
+    }
+    changes
+}
+
+/// Whether `field` only ever held nulls, so that its type is unknown.
+fn is_untyped(field: &Field) -> bool {
+    field.nullable && field.considered == 0
+}
+
+fn formats(data_type: &DataType) -> Option<Vec<String>> {
+    match data_type {
+        #[cfg(feature = "time")]
+        DataType::Date(date) => Some(date.formats.clone()),
+        #[cfg(feature = "time")]
+        DataType::Time(time) => Some(time.formats.clone()),
+        #[cfg(feature = "time")]
+        DataType::DateTime(date_time) => Some(
+            date_time
+                .formats
+                .iter()
+                .map(|format| match format {
+                    DateTimeFormat::Strftime(strftime) => strftime.clone(),
+                    format => format!("{format:?}"),
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn range<T: fmt::Display>((min, max): &(Option<T>, Option<T>)) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("{min} to {max}"),
+        _ => "none".to_string(),
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added => write!(f, "added"),
+            Change::Removed => write!(f, "removed"),
+            Change::Renamed { from, to } => write!(
+                f,
+                "renamed from {:?} to {:?}",
+                from.as_deref().unwrap_or_default(),
+                to.as_deref().unwrap_or_default()
+            ),
+            Change::Narrowed(names) => write!(f, "no longer {}", names.join(", ")),
+            Change::Widened(names) => write!(f, "now also {}", names.join(", ")),
+            Change::Nullable { to: true, .. } => write!(f, "now nullable"),
+            Change::Nullable { .. } => write!(f, "no longer nullable"),
+            Change::IntegerRange { from, to } => {
+                write!(f, "Integer range {} → {}", range(from), range(to))
+            }
+            Change::FloatRange { from, to } => {
+                write!(f, "Float range {} → {}", range(from), range(to))
+            }
+            Change::Formats {
+                data_type,
+                from,
+                to,
+            } => write!(
+                f,
+                "{data_type} formats {} → {}",
+                from.join(", "),
+                to.join(", ")
+            ),
+        }
+    }
+}
+
+/// Tabulates each change alongside the column it was to.
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = Builder::default();
+        builder.set_header(["column", "change"]);
+        for column in &self.columns {
+            let name = match (&column.name, column.new.or(column.old)) {
+                (Some(name), _) => name.clone(),
+                (None, Some(index)) => format!("#{index}"),
+                (None, None) => String::new(),
+            };
+            for change in &column.changes {
+                builder.push_record([name.clone(), change.to_string()]);
+            }
+        }
+        let mut table = builder.build();
+        table.with(Style::rounded());
+        write!(f, "{table}")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{diff::Change, infer_csv_with_options, CsvInput, InferOptions};
+
+    #[test]
+    fn drift() -> Result<(), Box<dyn Error>> {
+        let mut options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let old = infer_csv_with_options(
+            CsvInput::Value("id,score,joined,note\n1,0.5,2001-01-22,a\n2,1,2001-01-23,b\n"),
+            &mut options,
+        )?;
+        let new = infer_csv_with_options(
+            CsvInput::Value("id,points,joined,extra\n1,x,22/01/2001,\n7,,23/01/2001,\n"),
+            &mut options,
+        )?;
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        let changes: Vec<_> = diff
+            .columns
+            .iter()
+            .map(|column| (column.name.as_deref(), column.changes.clone()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    Some("id"),
+                    vec![
+                        Change::IntegerRange {
+                            from: (Some(1), Some(2)),
+                            to: (Some(1), Some(7)),
+                        },
+                        Change::FloatRange {
+                            from: (Some(1.0), Some(2.0)),
+                            to: (Some(1.0), Some(7.0)),
+                        },
+                    ]
+                ),
+                (
+                    Some("points"),
+                    vec![
+                        Change::Renamed {
+                            from: Some("score".into()),
+                            to: Some("points".into()),
+                        },
+                        Change::Narrowed(vec!["Float"]),
+                        Change::Nullable {
+                            from: false,
+                            to: true,
+                        },
+                    ]
+                ),
+                (
+                    Some("joined"),
+                    vec![Change::Formats {
+                        data_type: "Date",
+                        from: vec!["%Y-%m-%d".into()],
+                        to: vec!["%d/%m/%Y".into()],
+                    }]
+                ),
+                (
+                    Some("extra"),
+                    vec![
+                        Change::Renamed {
+                            from: Some("note".into()),
+                            to: Some("extra".into()),
+                        },
+                        Change::Nullable {
+                            from: false,
+                            to: true,
+                        },
+                    ]
+                ),
+            ],
+            changes
+        );
+        assert!(diff
+            .to_string()
+            .contains("renamed from \"score\" to \"points\""));
+        Ok(())
+    }
+
+    #[test]
+    fn all_null() -> Result<(), Box<dyn Error>> {
+        let mut options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let old = infer_csv_with_options(CsvInput::Value("id,note\n1,\n2,\n"), &mut options)?;
+        let new = infer_csv_with_options(CsvInput::Value("id,note\n1,a\n2,\n"), &mut options)?;
+        assert!(old.diff(&new).is_empty());
+        assert!(new.diff(&old).is_empty());
+
+        let new = infer_csv_with_options(CsvInput::Value("id,note\n1,a\n2,b\n"), &mut options)?;
+        let diff = old.diff(&new);
+        assert_eq!(
+            vec![Change::Nullable {
+                from: true,
+                to: false
+            }],
+            diff.columns[0].changes
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,121 @@
-use crate::{DataType, Validator};
+use std::mem::discriminant;
+
+use csv::Position;
+
+use crate::{report::Report, DataType, Merge, Validator};
 #[derive(Default, Debug, Clone)]
 pub struct Field {
     pub name: Option<String>,
+    /// The header of the column as it appears in the input, from which `name`
+    /// may have been [normalized](crate::InferOptions::header_case).
+    pub header: Option<String>,
+    /// Index of the column in the input, which differs from its index in
+    /// [`Fields`] when only some columns are
+    /// [selected](crate::InferOptions::columns). `None` for a field built by
+    /// hand, which is taken to be at its index in [`Fields`].
+    pub position: Option<usize>,
     pub valid_types: Vec<DataType>,
     pub nullable: bool,
+    /// Whether the type was fixed by an [`Override::Pin`](crate::csv::Override::Pin),
+    /// so that values are not validated against it.
+    pub pinned: bool,
+    /// The distinct values taken as null, in the order they were first seen.
+    /// An empty list means the field was never null, and `[""]` that it was
+    /// only ever null by way of empty values.
+    pub null_tokens: Vec<String>,
+    /// Whether the column was missing from some of the inputs in a
+    /// [union](Fields::union).
+    pub optional: bool,
+    /// Number of non-null values considered.
+    pub considered: u64,
+    /// Number of records without a value for this column, each taken as a
+    /// null: records of [flexible](crate::InferOptions::flexible) CSV that
+    /// ended before reaching it, or JSON objects without the key.
+    pub missing: u64,
+    /// Number of values in this column from records of
+    /// [flexible](crate::InferOptions::flexible) CSV longer than the header,
+    /// so always zero for the columns of the header itself.
+    pub extra: u64,
+    /// The candidate types that have been dropped, in the order they were.
+    pub eliminated: Vec<Elimination>,
+    /// How each of `valid_types` has fared, at the same index.
+    pub tallies: Vec<Tally>,
+}
+
+/// The value that eliminated a candidate type from a [`Field`].
+#[derive(Debug, Clone)]
+pub struct Elimination {
+    /// The [name](DataType::name) of the type.
+    pub data_type: &'static str,
+    pub value: String,
+    /// Where the record holding the value starts, when known.
+    pub position: Option<Position>,
+    /// How many values the type had matched before this one. For fields merged
+    /// from separate parts of the input, this only counts the values in the
+    /// part that eliminated the type.
+    pub matched: u64,
+}
+
+/// How many values a type may reject while still being considered valid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tolerance {
+    /// At most this many values.
+    Count(u64),
+    /// At most this share of the values considered, from 0 to 1. As the share
+    /// can only be known once every value has been considered, types are not
+    /// dropped for it until then.
+    Ratio(f64),
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance::Count(0)
+    }
+}
+
+impl Tolerance {
+    /// Whether `rejected` values out of `considered` are tolerated.
+    pub fn allows(&self, rejected: u64, considered: u64) -> bool {
+        match *self {
+            Tolerance::Count(count) => rejected <= count,
+            Tolerance::Ratio(ratio) => rejected as f64 <= ratio * considered as f64,
+        }
+    }
+}
+
+/// The values a candidate type of a [`Field`] has rejected so far.
+#[derive(Default, Debug, Clone)]
+pub struct Tally {
+    pub tolerance: Tolerance,
+    pub rejected: u64,
+    /// The first value rejected, which is reported as eliminating the type
+    /// should it be dropped.
+    pub first_rejected: Option<Elimination>,
+}
+
+impl Tally {
+    /// Whether the type is kept while values are still being considered.
+    fn holds(&self, considered: u64) -> bool {
+        matches!(self.tolerance, Tolerance::Ratio(_))
+            || self.tolerance.allows(self.rejected, considered)
+    }
+
+    fn merge(&mut self, other: Tally) {
+        self.rejected += other.rejected;
+        self.first_rejected = match (self.first_rejected.take(), other.first_rejected) {
+            (Some(a), Some(b)) if byte(&b) < byte(&a) => Some(b),
+            (a, b) => a.or(b),
+        };
+    }
+}
+
+fn byte(elimination: &Elimination) -> Option<u64> {
+    elimination.position.as_ref().map(Position::byte)
 }
 
 impl Field {
     pub fn new(name: Option<String>, valid_types: Vec<DataType>) -> Self {
-        Field {
-            name,
-            valid_types,
-            nullable: false,
-        }
+        Field::new_with_nullable(name, valid_types, false)
     }
 
     pub fn new_with_nullable(
@@ -21,15 +124,205 @@ impl Field {
         nullable: bool,
     ) -> Self {
         Field {
+            tallies: vec![Tally::default(); valid_types.len()],
             name,
+            header: None,
+            position: None,
+            pinned: false,
             valid_types,
             nullable,
+            null_tokens: vec![],
+            optional: false,
+            considered: 0,
+            missing: 0,
+            extra: 0,
+            eliminated: vec![],
         }
     }
 
+    /// Sets the tolerance of each candidate type.
+    pub fn with_tolerance(mut self, tolerance: impl Fn(&DataType) -> Tolerance) -> Self {
+        self.tallies = self
+            .valid_types
+            .iter()
+            .map(|data_type| Tally {
+                tolerance: tolerance(data_type),
+                ..Tally::default()
+            })
+            .collect();
+        self
+    }
+
+    /// The most specific valid type, taking any other over `Text`.
+    pub fn data_type(&self) -> Option<&DataType> {
+        self.valid_types
+            .iter()
+            .find(|data_type| !matches!(data_type, DataType::Text(_)))
+            .or(self.valid_types.first())
+    }
+
     pub fn consider(&mut self, value: &str) {
+        self.consider_at(value, None)
+    }
+
+    /// Notes that the field holds a null, written as `token`.
+    pub fn consider_null(&mut self, token: &str) {
+        self.nullable = true;
+        if !self.null_tokens.iter().any(|seen| seen == token) {
+            self.null_tokens.push(token.to_string())
+        }
+    }
+
+    /// Counts `records` that ended before reaching this column.
+    pub fn consider_missing(&mut self, records: u64) {
+        if records > 0 {
+            self.nullable = true;
+            self.missing += records;
+        }
+    }
+
+    /// Like [`Field::consider`], recording `position` as the place the value
+    /// was found should it eliminate any type.
+    pub fn consider_at(&mut self, value: &str, position: Option<&Position>) {
+        self.consider_where(value, position, |_| true)
+    }
+
+    /// Like [`Field::consider_at`], but for a value that only types for which
+    /// `applies` holds can take, such as a JSON number, which no date can.
+    /// The rest reject it.
+    pub fn consider_where(
+        &mut self,
+        value: &str,
+        position: Option<&Position>,
+        applies: impl Fn(&DataType) -> bool,
+    ) {
+        let matched = self.considered;
+        self.considered += 1;
+        if self.pinned {
+            return;
+        }
+        let considered = self.considered;
+        self.retain(|data_type, tally| {
+            if !applies(data_type) || !data_type.validate(value) {
+                tally.rejected += 1;
+                tally.first_rejected.get_or_insert_with(|| Elimination {
+                    data_type: data_type.name(),
+                    value: value.to_string(),
+                    position: position.cloned(),
+                    matched,
+                });
+            }
+            tally.holds(considered)
+        });
+    }
+
+    /// Drops any types rejecting more values than their ratio tolerates. This
+    /// is done once every value has been considered.
+    pub fn settle(&mut self) {
+        let considered = self.considered;
+        self.retain(|_, tally| tally.tolerance.allows(tally.rejected, considered));
+    }
+
+    /// The share of non-null values matched by each of `valid_types`, in the
+    /// same order.
+    pub fn match_ratios(&self) -> Vec<f64> {
         self.valid_types
-            .retain_mut(|data_type| data_type.validate(value))
+            .iter()
+            .enumerate()
+            .map(|(i, _)| match self.considered {
+                0 => 1.0,
+                considered => {
+                    let rejected = self.tallies.get(i).map_or(0, |tally| tally.rejected);
+                    (considered - rejected) as f64 / considered as f64
+                }
+            })
+            .collect()
+    }
+
+    /// Keeps the types for which `f` returns true, recording why the others
+    /// were eliminated. The types and their tallies are compacted in place,
+    /// so that a value every type takes costs no allocation.
+    fn retain(&mut self, mut f: impl FnMut(&mut DataType, &mut Tally) -> bool) {
+        // Fields built up by hand may well have no tallies.
+        self.tallies
+            .resize_with(self.valid_types.len(), Tally::default);
+        let mut kept = 0;
+        for i in 0..self.valid_types.len() {
+            if f(&mut self.valid_types[i], &mut self.tallies[i]) {
+                if kept != i {
+                    self.valid_types.swap(kept, i);
+                    self.tallies.swap(kept, i);
+                }
+                kept += 1;
+            } else if let Some(elimination) = self.tallies[i].first_rejected.take() {
+                self.eliminated.push(elimination);
+            }
+        }
+        self.valid_types.truncate(kept);
+        self.tallies.truncate(kept);
+    }
+
+    /// Merges a field inferred over another part of the same column, keeping
+    /// only the types valid in both.
+    pub fn merge(&mut self, mut other: Field) {
+        if self.name.is_none() {
+            self.name = other.name;
+        }
+        if self.header.is_none() {
+            self.header = other.header;
+        }
+        self.nullable |= other.nullable;
+        self.optional |= other.optional;
+        self.pinned |= other.pinned;
+        for token in other.null_tokens {
+            if !self.null_tokens.contains(&token) {
+                self.null_tokens.push(token)
+            }
+        }
+        self.considered += other.considered;
+        self.missing += other.missing;
+        self.extra += other.extra;
+        self.eliminated.append(&mut other.eliminated);
+
+        // Both fields narrow the same candidate list in order, so each type
+        // pairs with the next matching type in `other`, if it survived there.
+        other
+            .tallies
+            .resize_with(other.valid_types.len(), Tally::default);
+        let mut others: Vec<_> = other
+            .valid_types
+            .into_iter()
+            .zip(other.tallies)
+            .map(Some)
+            .collect();
+        let mut start = 0;
+        let considered = self.considered;
+        self.retain(|data_type, tally| {
+            let position = others[start..].iter().position(|other| {
+                other
+                    .as_ref()
+                    .is_some_and(|(other, _)| discriminant(other) == discriminant(data_type))
+            });
+            match position {
+                Some(position) => {
+                    start += position + 1;
+                    let (other, other_tally) = others[start - 1].take().unwrap();
+                    tally.merge(other_tally);
+                    data_type.merge(other) && tally.holds(considered)
+                }
+                None => false,
+            }
+        });
+
+        // The earliest value to eliminate a type is the one it would have met
+        // first had the input been considered in one go.
+        self.eliminated.sort_by_key(byte);
+        let mut seen = vec![];
+        self.eliminated.retain(|elimination| {
+            let first = !seen.contains(&elimination.data_type);
+            seen.push(elimination.data_type);
+            first
+        });
     }
 }
 
@@ -39,22 +332,115 @@ use std::{
 };
 use tabled::{builder::Builder, settings::Style};
 
-pub struct Fields(pub Vec<Field>);
+/// The fields inferred from an input, along with a [`Report`] on how they were
+/// inferred. Derefs to the fields; build one with [`Fields::new`] or `into`.
+#[derive(Default, Debug, Clone)]
+pub struct Fields {
+    pub fields: Vec<Field>,
+    pub report: Report,
+}
 
 impl Deref for Fields {
     type Target = Vec<Field>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.fields
     }
 }
 
 impl DerefMut for Fields {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.fields
     }
 }
 
+impl From<Vec<Field>> for Fields {
+    fn from(fields: Vec<Field>) -> Self {
+        Fields::new(fields)
+    }
+}
+
+impl Fields {
+    pub fn new(fields: Vec<Field>) -> Self {
+        Fields {
+            fields,
+            report: Report::default(),
+        }
+    }
+
+    /// Merges fields inferred over another part of the same input. Fields are
+    /// paired by position, and any extra fields in `other` are appended.
+    pub fn merge(&mut self, other: Fields) {
+        // A column found in only one part was missing from every record of the
+        // other.
+        let (records, other_records) = (self.report.records, other.report.records);
+        for field in self.iter_mut().skip(other.len()) {
+            field.consider_missing(other_records);
+        }
+        let mut others = other.fields.into_iter();
+        for (field, other) in self.iter_mut().zip(others.by_ref()) {
+            field.merge(other)
+        }
+        self.extend(others.map(|mut field| {
+            field.consider_missing(records);
+            field
+        }));
+        self.report.merge(other.report);
+    }
+
+    /// Combines the fields inferred from several inputs of the same shape,
+    /// such as a feed split across files, into one schema.
+    ///
+    /// Columns are matched by name, or by position where they have none, and
+    /// keep only the types valid in every input. Columns missing from any of
+    /// the inputs are marked [optional](Field::optional).
+    pub fn union(schemas: impl IntoIterator<Item = Fields>) -> Fields {
+        let mut schemas = schemas.into_iter();
+        let Some(mut union) = schemas.next() else {
+            return Fields::default();
+        };
+        for schema in schemas {
+            let mut matched = vec![false; union.len()];
+            for (i, field) in schema.fields.into_iter().enumerate() {
+                let index = match field.name {
+                    Some(ref name) => union
+                        .iter()
+                        .position(|other| other.name.as_ref() == Some(name)),
+                    None => (i < union.len() && union[i].name.is_none()).then_some(i),
+                };
+                match index {
+                    Some(index) if !matched[index] => {
+                        matched[index] = true;
+                        union[index].merge(field);
+                    }
+                    _ => union.push(Field {
+                        optional: true,
+                        ..field
+                    }),
+                }
+            }
+            for (field, matched) in union.iter_mut().zip(matched) {
+                field.optional |= !matched;
+            }
+            union.report.merge(schema.report);
+        }
+        union
+    }
+}
+
+impl fmt::Display for Elimination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ✗ {:?} (", self.data_type, self.value)?;
+        if let Some(ref position) = self.position {
+            write!(f, "line {}, ", position.line())?;
+        }
+        write!(f, "{} matched)", self.matched)
+    }
+}
+
+/// Tabulates the valid types of each field. The alternate form, `{:#}`, also
+/// gives the match ratio of types that rejected any values, the tokens taken
+/// as null, and the value that eliminated each dropped type.
 impl fmt::Display for Fields {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut builder = Builder::default();
@@ -72,28 +458,165 @@ impl fmt::Display for Fields {
             builder.push_record(
                 self.iter()
                     .map(|field| {
-                        field
-                            .valid_types
-                            .get(i)
-                            .map_or("", |data_type| match data_type {
-                                DataType::Text(_) => "Text",
-                                DataType::Integer(_) => "Integer",
-                                DataType::Float(_) => "Float",
-                                DataType::Empty(_) => "Empty",
-                                DataType::Literal(_) => "Literal",
-                                DataType::Unique(_) => "Unique",
-                                DataType::Date(_) => "Date",
-                                DataType::Time(_) => "Time",
-                                DataType::DateTime(_) => "DateTime",
-                                #[cfg(feature = "python")]
-                                DataType::Py(_) => "PyObject",
-                            })
+                        let Some(data_type) = field.valid_types.get(i) else {
+                            return String::new();
+                        };
+                        match field.match_ratios()[i] {
+                            ratio if f.alternate() && ratio < 1.0 => {
+                                format!("{} ({:.2}%)", data_type.name(), ratio * 100.0)
+                            }
+                            _ => data_type.name().to_string(),
+                        }
                     })
                     .collect::<Vec<_>>(),
             );
         }
+        if f.alternate() {
+            if self.iter().any(|field| field.nullable) {
+                builder.push_record(self.iter().map(|field| match field.null_tokens.is_empty() {
+                    true if field.nullable => "null".to_string(),
+                    true => String::new(),
+                    false => format!(
+                            "null: {}",
+                            field
+                                .null_tokens
+                                .iter()
+                                .map(|token| format!("{token:?}"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                }));
+            }
+            if self.iter().any(|field| field.missing > 0) {
+                builder.push_record(self.iter().map(|field| match field.missing {
+                    0 => String::new(),
+                    missing => format!("missing from {missing} records"),
+                }));
+            }
+            if self.iter().any(|field| field.extra > 0) {
+                builder.push_record(self.iter().map(|field| match field.extra {
+                    0 => String::new(),
+                    extra => format!("past the header in {extra} records"),
+                }));
+            }
+            for i in 0..(self
+                .iter()
+                .map(|field| field.eliminated.len())
+                .max()
+                .unwrap_or_default())
+            {
+                builder.push_record(
+                    self.iter()
+                        .map(|field| {
+                            field
+                                .eliminated
+                                .get(i)
+                                .map_or(String::new(), Elimination::to_string)
+                        })
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
         let mut table = builder.build();
         table.with(Style::rounded());
         write!(f, "{table}")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{infer_csv_with_options, CsvInput, DataType, Fields, InferOptions, Tolerance};
+
+    #[test]
+    fn eliminated() -> Result<(), Box<dyn Error>> {
+        let fields = infer_csv_with_options(
+            CsvInput::Value("id,score\n1,2\n2,0.5\n3,x\n"),
+            &mut InferOptions {
+                has_headers: true,
+                ..InferOptions::default()
+            },
+        )?;
+        let eliminated = &fields[1].eliminated;
+        assert_eq!(
+            vec!["Date", "Time", "DateTime", "Integer", "Float"],
+            eliminated
+                .iter()
+                .map(|elimination| elimination.data_type)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!("0.5", eliminated[3].value);
+        assert_eq!(1, eliminated[3].matched);
+        assert_eq!("x", eliminated[4].value);
+        assert_eq!(2, eliminated[4].matched);
+        assert_eq!(4, eliminated[4].position.as_ref().unwrap().line());
+
+        assert!(!fields.to_string().contains('✗'));
+        assert!(format!("{fields:#}").contains(r#"Float ✗ "x" (line 4, 2 matched)"#));
+        Ok(())
+    }
+
+    #[test]
+    fn union() -> Result<(), Box<dyn Error>> {
+        let mut options = InferOptions {
+            has_headers: true,
+            ..InferOptions::default()
+        };
+        let schemas = [
+            "id,score\n1,2\n",
+            "score,id,note\n0.5,2,a\n",
+            "note,id\nb,\n",
+        ]
+        .into_iter()
+        .map(|value| infer_csv_with_options(CsvInput::Value(value), &mut options))
+        .collect::<Result<Vec<_>, _>>()?;
+        let fields = Fields::union(schemas);
+
+        assert_eq!(
+            vec![Some("id"), Some("score"), Some("note")],
+            fields
+                .iter()
+                .map(|field| field.name.as_deref())
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+        assert!(fields[0].nullable && !fields[0].optional);
+        assert!(matches!(fields[1].valid_types[0], DataType::Float(_)));
+        assert!(fields[1].optional && fields[2].optional);
+        assert_eq!(3, fields.report.records);
+        Ok(())
+    }
+
+    #[test]
+    fn tolerance() -> Result<(), Box<dyn Error>> {
+        let mut value = (0..999).map(|i| format!("{i}\n")).collect::<String>();
+        value.insert_str(10, "N/A\n");
+        let mut options = InferOptions {
+            tolerance: Tolerance::Count(1),
+            ..InferOptions::default()
+        };
+        let fields = infer_csv_with_options(CsvInput::Value(&value), &mut options)?;
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+        assert_eq!(0.999, fields[0].match_ratios()[0]);
+        assert!(format!("{fields:#}").contains("Integer (99.90%)"));
+
+        options.tolerance = Tolerance::Ratio(0.0005);
+        let fields = infer_csv_with_options(CsvInput::Value(&value), &mut options)?;
+        assert!(matches!(fields[0].valid_types[0], DataType::Text(_)));
+        let integer = fields[0]
+            .eliminated
+            .iter()
+            .find(|elimination| elimination.data_type == "Integer")
+            .unwrap();
+        assert_eq!(("N/A", 5), (integer.value.as_str(), integer.matched));
+
+        options
+            .tolerances
+            .insert("Integer".into(), Tolerance::Ratio(0.01));
+        let fields = infer_csv_with_options(CsvInput::Value(&value), &mut options)?;
+        assert!(matches!(fields[0].valid_types[0], DataType::Integer(_)));
+        assert!(matches!(fields[0].valid_types[1], DataType::Text(_)));
+        Ok(())
+    }
+}
@@ -0,0 +1,326 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{BufRead, BufReader},
+};
+
+use csv::Position;
+use serde_json::{Map, Value};
+
+use crate::{
+    csv::{is_null, keep, new_field, open, reject, Sample, Sampler, Tracker},
+    field::Fields,
+    CsvInput, DataType, Field, InferOptions, Literal,
+};
+
+mod document;
+pub use document::{infer_json, DocumentSchema, Kind, Schema};
+
+/// The default [`InferOptions::data_types`] with a [`Literal`] for JSON
+/// booleans ahead of `Text`, so that columns of them are told apart.
+pub fn json_data_types() -> Vec<DataType> {
+    let mut data_types = InferOptions::default().data_types;
+    let text = data_types
+        .iter()
+        .position(|data_type| matches!(data_type, DataType::Text(_)))
+        .unwrap_or(data_types.len());
+    data_types.insert(
+        text,
+        DataType::Literal(Literal::new(vec!["true".into(), "false".into()])),
+    );
+    data_types
+}
+
+/// Infers the fields of `input` given as JSON Lines, with one JSON object on
+/// each line.
+///
+/// Each key becomes a field, named by its path through any nested objects
+/// with the keys joined by dots, in the order the keys are first seen. Arrays
+/// are taken as text. Objects without a key count as
+/// [missing](Field::missing) for it.
+///
+/// Strings are considered against every candidate type, just as CSV values
+/// are. Numbers are only considered against types other than dates and
+/// times, and booleans only against types other than those and numbers,
+/// such as the [`Literal`] in [`json_data_types`]. Nulls, and strings taken
+/// as null, make a field nullable.
+///
+/// Of the CSV options, those for the dialect, headers, trimming and framing
+/// do not apply. A line that is not a JSON object is a malformed record.
+pub fn infer_json_lines(
+    input: CsvInput,
+    options: &mut InferOptions,
+) -> Result<Fields, Box<dyn Error>> {
+    let (reader, encoding) = open(input, options)?;
+    let mut reader = BufReader::new(reader);
+    let mut inference = JsonInference::new(options);
+    let mut sampler = Sampler::new(options.sampling);
+    let mut reservoir = vec![];
+    let mut tracker = Tracker::new(options.progress.as_ref());
+    let mut incomplete = false;
+    let mut line = vec![];
+    let (mut byte, mut line_number, mut record) = (0, 0, 0);
+
+    while !sampler.done() {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        let mut position = Position::new();
+        position.set_byte(byte).set_line(line_number + 1);
+        byte += read as u64;
+        line_number += 1;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        position.set_record(record);
+        record += 1;
+
+        match serde_json::from_slice(&line) {
+            Ok(Value::Object(object)) => match sampler.sample(Some(position.byte())) {
+                Sample::Consider => inference.consider(&object, &position),
+                Sample::Skip => (),
+                Sample::Keep(slot) => keep(&mut reservoir, slot, (object, position)),
+            },
+            Ok(_) => inference.reject(position, "expected a JSON object".into())?,
+            Err(err) => inference.reject(position, err.into())?,
+        }
+        if tracker.record(byte).is_break() {
+            incomplete = true;
+            break;
+        }
+    }
+    for (object, position) in reservoir {
+        inference.consider(&object, &position);
+    }
+
+    let mut fields = inference.fields;
+    fields.iter_mut().for_each(Field::settle);
+    fields.report.encoding = Some(encoding);
+    fields.report.sampling = options.sampling;
+    fields.report.incomplete = incomplete;
+    Ok(fields)
+}
+
+/// Fields being inferred from JSON objects.
+struct JsonInference<'a> {
+    options: &'a InferOptions,
+    null_validator: DataType,
+    fields: Fields,
+    /// Index among the fields of each path seen, or `None` for those not
+    /// [selected](InferOptions::columns).
+    paths: HashMap<String, Option<usize>>,
+}
+
+impl<'a> JsonInference<'a> {
+    fn new(options: &'a InferOptions) -> Self {
+        JsonInference {
+            options,
+            null_validator: options.null_validator.clone(),
+            fields: Fields::default(),
+            paths: HashMap::new(),
+        }
+    }
+
+    fn reject(&mut self, position: Position, error: Box<dyn Error>) -> Result<(), Box<dyn Error>> {
+        reject(
+            self.options,
+            &mut self.fields.report.rejected,
+            Some(position),
+            error,
+        )
+    }
+
+    fn consider(&mut self, object: &Map<String, Value>, position: &Position) {
+        let mut values = vec![];
+        flatten(object, "", &mut values);
+        let report = &mut self.fields.report;
+        if report.records == 0 {
+            report.columns = values.len();
+        }
+        *report.row_lengths.entry(values.len()).or_default() += 1;
+
+        let mut seen = vec![false; self.fields.len()];
+        for (path, value) in values {
+            let Some(index) = self.index(path) else {
+                continue;
+            };
+            seen.resize(self.fields.len(), false);
+            seen[index] = true;
+            consider_value(
+                self.options,
+                &mut self.null_validator,
+                &mut self.fields[index],
+                value,
+                Some(position),
+            );
+        }
+        for (field, seen) in self.fields.iter_mut().zip(seen) {
+            if !seen {
+                field.consider_missing(1);
+            }
+        }
+        self.fields.report.records += 1;
+    }
+
+    /// The index of the field for `path`, adding one if it is new and
+    /// selected.
+    fn index(&mut self, path: String) -> Option<usize> {
+        if let Some(&index) = self.paths.get(&path) {
+            return index;
+        }
+        let position = self.paths.len();
+        let selected = self.options.columns.as_ref().is_none_or(|selectors| {
+            selectors
+                .iter()
+                .any(|selector| selector.matches(position, Some(&path)))
+        });
+        let index = selected.then(|| {
+            let mut field = new_field(self.options, position, Some(&path), Some(path.clone()));
+            // Every object before this one was without it.
+            field.consider_missing(self.fields.report.records);
+            self.fields.push(field);
+            self.fields.len() - 1
+        });
+        self.paths.insert(path, index);
+        index
+    }
+}
+
+/// Collects the values of `object` by their dotted paths below `prefix`,
+/// descending into nested objects.
+fn flatten<'a>(
+    object: &'a Map<String, Value>,
+    prefix: &str,
+    values: &mut Vec<(String, &'a Value)>,
+) {
+    for (key, value) in object {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(object) if !object.is_empty() => flatten(object, &path, values),
+            value => values.push((path, value)),
+        }
+    }
+}
+
+/// Considers a JSON value for `field`, against the types that can take its
+/// kind of value.
+fn consider_value(
+    options: &InferOptions,
+    null_validator: &mut DataType,
+    field: &mut Field,
+    value: &Value,
+    position: Option<&Position>,
+) {
+    match value {
+        Value::Null => field.consider_null("null"),
+        Value::String(value) => {
+            if is_null(options, null_validator, value) {
+                field.consider_null(value)
+            } else {
+                field.consider_at(value, position)
+            }
+        }
+        Value::Number(number) => field.consider_where(&number.to_string(), position, |data_type| {
+            !is_temporal(data_type)
+        }),
+        Value::Bool(value) => field.consider_where(&value.to_string(), position, |data_type| {
+            !is_temporal(data_type) && !is_numeric(data_type)
+        }),
+        Value::Array(_) | Value::Object(_) => {
+            field.consider_where(&value.to_string(), position, is_textual)
+        }
+    }
+}
+
+/// Whether `data_type` can take any text, such as a JSON array written out.
+fn is_textual(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Text(_) | DataType::Unique(_))
+}
+
+fn is_temporal(data_type: &DataType) -> bool {
+    matches!(data_type.name(), "Date" | "Time" | "DateTime")
+}
+
+fn is_numeric(data_type: &DataType) -> bool {
+    matches!(data_type, DataType::Integer(_) | DataType::Float(_))
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{
+        json::{infer_json_lines, json_data_types},
+        CsvInput, DataType, InferOptions,
+    };
+
+    #[test]
+    fn lines() -> Result<(), Box<dyn Error>> {
+        let value = r#"{"id": 1, "name": "Ferris", "joined": "2015-05-15", "crab": true, "home": {"city": "Oslo"}}
+{"id": 20150515, "name": null, "joined": "2015-05-16", "crab": false, "tags": ["a"]}
+
+{"id": 3, "name": "Corro", "joined": "", "crab": true, "home": {"city": "Bergen", "zip": 5003}}
+"#;
+        let fields = infer_json_lines(
+            CsvInput::Value(value),
+            &mut InferOptions {
+                data_types: json_data_types(),
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(
+            vec![
+                "id",
+                "name",
+                "joined",
+                "crab",
+                "home.city",
+                "tags",
+                "home.zip"
+            ],
+            fields
+                .iter()
+                .map(|field| field.name.as_deref().unwrap())
+                .collect::<Vec<_>>()
+        );
+        let types: Vec<_> = fields
+            .iter()
+            .map(|field| field.data_type().map(DataType::name))
+            .collect();
+        assert_eq!(Some("Integer"), types[0]);
+        assert_eq!(Some("Literal"), types[3]);
+        assert_eq!(Some("Text"), types[4]);
+        #[cfg(feature = "time")]
+        assert_eq!(Some("Date"), types[2]);
+        assert!(fields[1].nullable && fields[2].nullable);
+        assert_eq!(
+            (1, 2, 2),
+            (fields[4].missing, fields[5].missing, fields[6].missing)
+        );
+        assert_eq!(3, fields.report.records);
+
+        let value = "{\"id\": 1}\n[1]\n";
+        assert!(infer_json_lines(CsvInput::Value(value), &mut InferOptions::default()).is_err());
+        let fields = infer_json_lines(
+            CsvInput::Value(value),
+            &mut InferOptions {
+                max_errors: Some(1),
+                ..InferOptions::default()
+            },
+        )?;
+        assert_eq!(
+            Some(2),
+            fields.report.rejected[0]
+                .position
+                .as_ref()
+                .map(|p| p.line())
+        );
+        Ok(())
+    }
+}
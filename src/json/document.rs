@@ -0,0 +1,332 @@
+use std::{error::Error, fmt};
+
+use serde_json::Value;
+
+use crate::{
+    csv::{is_null, keep, new_field, open, Sample, Sampler},
+    json::{consider_value, is_textual},
+    report::Report,
+    CsvInput, DataType, Field, InferOptions, Text,
+};
+
+/// The schema of the values at one place in a JSON document.
+#[derive(Default, Debug, Clone)]
+pub struct Schema {
+    /// What the values are, once any that are not null have been seen.
+    pub kind: Option<Kind>,
+    /// Whether any of the values were null, or strings taken as null.
+    pub nullable: bool,
+    /// Number of objects without this key, for the fields of a
+    /// [`Kind::Struct`].
+    pub missing: u64,
+    /// Number of values considered, not counting nulls.
+    pub values: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum Kind {
+    /// Strings, numbers or booleans, inferred as a CSV column is. Values that
+    /// are sometimes objects or arrays and sometimes not are taken as text.
+    Scalar(Field),
+    /// Objects, with the schema of each key in the order first seen.
+    Struct(Vec<(String, Schema)>),
+    /// Arrays, with the schema of all their items.
+    List(Box<Schema>),
+}
+
+/// The schema of the records of a JSON document. See [`infer_json`].
+#[derive(Debug, Clone)]
+pub struct DocumentSchema {
+    pub records: Schema,
+    /// How many records there were, and the encoding of the document.
+    pub report: Report,
+}
+
+/// Infers the schema of the array of records at `pointer` in the JSON
+/// document `input`, such as `""` for a document that is itself an array of
+/// records, or `"/data/items"` for one nested in an API response.
+///
+/// Unlike [`infer_json_lines`](crate::json::infer_json_lines), nested objects
+/// and arrays are kept as [`Kind::Struct`] and [`Kind::List`] nodes. Scalars
+/// are considered as they are there, and `options` apply likewise, except
+/// that [`InferOptions::overrides`] match dotted paths from the record, with
+/// `[]` for the items of an array, and [`InferOptions::columns`] is ignored.
+///
+/// [`InferOptions::sampling`] picks among the records, except that
+/// [`SamplingStrategy::FirstBytes`](crate::csv::SamplingStrategy::FirstBytes)
+/// takes them all, as the whole document is read before the records are
+/// found. [`InferOptions::max_errors`] and [`InferOptions::progress`] are
+/// ignored, as a document that does not parse has no records to skip.
+pub fn infer_json(
+    input: CsvInput,
+    pointer: &str,
+    options: &mut InferOptions,
+) -> Result<DocumentSchema, Box<dyn Error>> {
+    let (reader, encoding) = open(input, options)?;
+    let document: Value = serde_json::from_reader(reader)?;
+    let records = match document.pointer(pointer) {
+        Some(Value::Array(records)) => records,
+        Some(_) => return Err(format!("expected an array of records at {pointer:?}").into()),
+        None => return Err(format!("no value at {pointer:?}").into()),
+    };
+
+    let mut inference = DocumentInference {
+        options,
+        null_validator: options.null_validator.clone(),
+    };
+    let mut schema = Schema::default();
+    let mut sampler = Sampler::new(options.sampling);
+    let mut reservoir = vec![];
+    let mut considered = 0;
+    for record in records {
+        if sampler.done() {
+            break;
+        }
+        match sampler.sample(None) {
+            Sample::Consider => {
+                inference.consider(&mut schema, "", "", record);
+                considered += 1;
+            }
+            Sample::Skip => (),
+            Sample::Keep(slot) => keep(&mut reservoir, slot, record),
+        }
+    }
+    for record in reservoir {
+        inference.consider(&mut schema, "", "", record);
+        considered += 1;
+    }
+    schema.settle();
+    Ok(DocumentSchema {
+        records: schema,
+        report: Report {
+            encoding: Some(encoding),
+            sampling: options.sampling,
+            records: considered,
+            ..Report::default()
+        },
+    })
+}
+
+struct DocumentInference<'a> {
+    options: &'a InferOptions,
+    null_validator: DataType,
+}
+
+impl DocumentInference<'_> {
+    /// Considers `value` for the schema at `path`, where the value has `name`.
+    fn consider(&mut self, schema: &mut Schema, path: &str, name: &str, value: &Value) {
+        let null = match value {
+            Value::Null => true,
+            Value::String(value) => is_null(self.options, &mut self.null_validator, value),
+            _ => false,
+        };
+        if null {
+            schema.nullable = true;
+            if let Some(Kind::Scalar(ref mut field)) = schema.kind {
+                consider_value(self.options, &mut self.null_validator, field, value, None);
+            }
+            return;
+        }
+        schema.values += 1;
+
+        let kind = schema.kind.get_or_insert_with(|| match value {
+            Value::Object(_) => Kind::Struct(vec![]),
+            Value::Array(_) => Kind::List(Box::default()),
+            _ => Kind::Scalar(new_field(
+                self.options,
+                0,
+                Some(path),
+                Some(name.to_string()),
+            )),
+        });
+        match (kind, value) {
+            (Kind::Struct(fields), Value::Object(object)) => {
+                for (key, value) in object {
+                    let index = match fields.iter().position(|(other, _)| other == key) {
+                        Some(index) => index,
+                        None => {
+                            // Every object before this one was without it.
+                            let missing = schema.values - 1;
+                            let child = Schema {
+                                missing,
+                                ..Schema::default()
+                            };
+                            fields.push((key.clone(), child));
+                            fields.len() - 1
+                        }
+                    };
+                    let path = join(path, key);
+                    self.consider(&mut fields[index].1, &path, key, value);
+                }
+                for (key, child) in fields.iter_mut() {
+                    if !object.contains_key(key) {
+                        child.missing += 1;
+                    }
+                }
+            }
+            (Kind::List(items), Value::Array(values)) => {
+                let path = join(path, "[]");
+                for value in values {
+                    self.consider(items, &path, name, value);
+                }
+            }
+            (Kind::Scalar(field), value) => {
+                consider_value(self.options, &mut self.null_validator, field, value, None);
+            }
+            (kind, value) => {
+                // Neither an object nor an array every time, so all that can
+                // be said is that it can be written out as text.
+                let mut field = Field::new(
+                    Some(name.to_string()),
+                    vec![DataType::Text(Text::default())],
+                );
+                field.considered = schema.values - 1;
+                field.consider_where(&value.to_string(), None, is_textual);
+                *kind = Kind::Scalar(field);
+            }
+        }
+    }
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+impl Schema {
+    /// Applies the ratio tolerances of every scalar below this schema. See
+    /// [`Field::settle`].
+    fn settle(&mut self) {
+        match self.kind {
+            Some(Kind::Scalar(ref mut field)) => field.settle(),
+            Some(Kind::Struct(ref mut fields)) => {
+                fields.iter_mut().for_each(|(_, schema)| schema.settle())
+            }
+            Some(Kind::List(ref mut items)) => items.settle(),
+            None => (),
+        }
+    }
+
+    /// The schema at a dotted `path` below this one, with `[]` for the items
+    /// of an array.
+    pub fn get(&self, path: &str) -> Option<&Schema> {
+        path.split('.')
+            .filter(|key| !key.is_empty())
+            .try_fold(self, |schema, key| match (&schema.kind, key) {
+                (Some(Kind::List(items)), "[]") => Some(items.as_ref()),
+                (Some(Kind::Struct(fields)), key) => fields
+                    .iter()
+                    .find(|(other, _)| other == key)
+                    .map(|(_, schema)| schema),
+                _ => None,
+            })
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        match self.kind {
+            None => write!(f, "null")?,
+            Some(Kind::Scalar(ref field)) => {
+                write!(f, "{}", field.data_type().map_or("none", DataType::name))?
+            }
+            Some(Kind::List(ref items)) => {
+                write!(f, "[")?;
+                items.write(f, depth)?;
+                write!(f, "]")?;
+            }
+            Some(Kind::Struct(ref fields)) => {
+                writeln!(f, "{{")?;
+                for (key, schema) in fields {
+                    write!(f, "{:indent$}{key}", "", indent = (depth + 1) * 2)?;
+                    if schema.missing > 0 {
+                        write!(f, "?")?;
+                    }
+                    write!(f, ": ")?;
+                    schema.write(f, depth + 1)?;
+                    writeln!(f)?;
+                }
+                write!(f, "{:indent$}}}", "", indent = depth * 2)?;
+            }
+        }
+        if self.nullable && self.kind.is_some() {
+            write!(f, " | null")?;
+        }
+        Ok(())
+    }
+}
+
+/// Outlines the schema, marking keys that objects can be without with `?`.
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+
+    use crate::{
+        csv::SamplingStrategy,
+        json::{infer_json, json_data_types, Kind},
+        CsvInput, DataType, InferOptions,
+    };
+
+    #[test]
+    fn document() -> Result<(), Box<dyn Error>> {
+        let value = r#"{
+            "page": 1,
+            "data": {"items": [
+                {"id": 1, "name": "Ferris", "home": {"city": "Oslo"}, "tags": ["a", "b"]},
+                {"id": 2, "name": null, "tags": [], "scores": [{"at": "2015-05-15", "n": 0.5}]},
+                {"id": 3, "name": "Corro", "home": null, "tags": ["c"], "scores": []}
+            ]}
+        }"#;
+        let mut options = InferOptions {
+            data_types: json_data_types(),
+            ..InferOptions::default()
+        };
+        let schema = infer_json(CsvInput::Value(value), "/data/items", &mut options)?;
+        assert_eq!(3, schema.report.records);
+        let records = &schema.records;
+        let Some(Kind::Struct(ref fields)) = records.kind else {
+            panic!("expected a struct, got {records}");
+        };
+        assert_eq!(
+            vec!["id", "name", "home", "tags", "scores"],
+            fields
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let scalar = |path| match records.get(path).and_then(|schema| schema.kind.as_ref()) {
+            Some(Kind::Scalar(field)) => field.data_type().map(DataType::name),
+            _ => None,
+        };
+        assert_eq!(Some("Integer"), scalar("id"));
+        assert_eq!(Some("Text"), scalar("tags.[]"));
+        assert_eq!(Some("Float"), scalar("scores.[].n"));
+        #[cfg(feature = "time")]
+        assert_eq!(Some("Date"), scalar("scores.[].at"));
+
+        let home = records.get("home").unwrap();
+        assert!(home.nullable);
+        assert_eq!(1, home.missing);
+        assert!(records.get("name").unwrap().nullable);
+        assert_eq!(1, records.get("scores").unwrap().missing);
+        assert!(records.to_string().contains("scores?: [{"));
+
+        options.sampling = SamplingStrategy::FirstRows(1);
+        let schema = infer_json(CsvInput::Value(value), "/data/items", &mut options)?;
+        assert_eq!(1, schema.report.records);
+        assert_eq!(1, schema.records.values);
+        assert!(schema.records.get("scores").is_none());
+
+        assert!(infer_json(CsvInput::Value(value), "/page", &mut options).is_err());
+        assert!(infer_json(CsvInput::Value(value), "/missing", &mut options).is_err());
+        Ok(())
+    }
+}
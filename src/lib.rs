@@ -1,11 +1,23 @@
+mod diff;
 mod field;
+mod report;
 pub mod validators;
 
-pub use field::{Field, Fields};
-pub use validators::{DataType, Empty, Float, Integer, Literal, Text, Unique, Validator};
+pub use diff::{Change, ColumnDiff, SchemaDiff};
+pub use field::{Elimination, Field, Fields, Tally, Tolerance};
+pub use report::{RejectedRecord, Report};
+pub use validators::{DataType, Empty, Float, Integer, Literal, Merge, Text, Unique, Validator};
 
 #[cfg(feature = "time")]
 pub use validators::{Date, DateTime, Time};
 
 pub mod csv;
-pub use crate::csv::{infer_csv, infer_csv_with_options, CsvInput, InferOptions};
+#[cfg(feature = "json")]
+pub mod json;
+pub use crate::csv::{
+    infer_csv, infer_csv_auto, infer_csv_files, infer_csv_parallel, infer_csv_with_options, sniff,
+    validate_csv, ColumnSelector, Compression, CsvInput, Dialect, Encoding, HeaderCase,
+    InferOptions, InferSession, Progress, ValidateOptions, ValidationReport, COMMON_NULL_TOKENS,
+};
+#[cfg(feature = "json")]
+pub use json::{infer_json, infer_json_lines, json_data_types};
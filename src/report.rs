@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use csv::Position;
+
+use crate::csv::{Encoding, HeaderDetection, SamplingStrategy};
+
+/// What was found while reading the input, beyond the fields themselves.
+#[derive(Default, Debug, Clone)]
+pub struct Report {
+    /// How the header row was decided on, when
+    /// [`InferOptions::detect_headers`](crate::InferOptions::detect_headers)
+    /// is set.
+    pub header: Option<HeaderDetection>,
+    /// The encoding the input was read in, when read rather than pushed to an
+    /// [`InferSession`](crate::InferSession).
+    pub encoding: Option<Encoding>,
+    /// How records were picked to be considered.
+    pub sampling: SamplingStrategy,
+    /// How many columns the first record of the input has, whether or not they
+    /// were all [selected](crate::InferOptions::columns).
+    pub columns: usize,
+    /// How many data records were considered.
+    pub records: u64,
+    /// How many records before the table were skipped, by
+    /// [`InferOptions::skip_lines`](crate::InferOptions::skip_lines),
+    /// [`InferOptions::header_row`](crate::InferOptions::header_row) or as a
+    /// [preamble](crate::InferOptions::detect_preamble). Comments are not
+    /// counted.
+    pub preamble: u64,
+    /// How many records at the end of the input were skipped as a
+    /// [footer](crate::InferOptions::detect_footer).
+    pub footer: u64,
+    /// How many of the records considered had each number of fields.
+    pub row_lengths: BTreeMap<usize, u64>,
+    /// Malformed records that were skipped rather than failing inference, in
+    /// input order. See
+    /// [`InferOptions::max_errors`](crate::InferOptions::max_errors).
+    pub rejected: Vec<RejectedRecord>,
+    /// Whether inference was stopped early by
+    /// [`InferOptions::progress`](crate::InferOptions::progress), leaving
+    /// the rest of the input unread.
+    pub incomplete: bool,
+}
+
+/// A malformed record skipped during inference.
+#[derive(Debug, Clone)]
+pub struct RejectedRecord {
+    /// Where the record starts, with its byte offset and line number, when
+    /// known.
+    pub position: Option<Position>,
+    /// Why the record was rejected.
+    pub error: String,
+}
+
+impl Report {
+    /// How many of the records considered had fewer fields than the first.
+    pub fn short_rows(&self) -> u64 {
+        self.row_lengths.range(..self.columns).map(|(_, n)| n).sum()
+    }
+
+    /// How many of the records considered had more fields than the first.
+    pub fn long_rows(&self) -> u64 {
+        self.row_lengths
+            .range(self.columns + 1..)
+            .map(|(_, n)| n)
+            .sum()
+    }
+
+    /// Merges the report for another part of the same input.
+    pub fn merge(&mut self, other: Report) {
+        if self.header.is_none() {
+            self.header = other.header;
+        }
+        if self.encoding.is_none() {
+            self.encoding = other.encoding;
+        }
+        self.columns = self.columns.max(other.columns);
+        self.records += other.records;
+        self.preamble += other.preamble;
+        self.footer += other.footer;
+        for (length, records) in other.row_lengths {
+            *self.row_lengths.entry(length).or_default() += records;
+        }
+        self.incomplete |= other.incomplete;
+        self.rejected.extend(other.rejected);
+        self.rejected
+            .sort_by_key(|rejected| rejected.position.as_ref().map(Position::byte));
+    }
+}
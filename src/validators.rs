@@ -1,4 +1,4 @@
-use baskerville_macro::Validator;
+use baskerville_macro::{Merge, Validator};
 
 #[cfg(feature = "python")]
 use pyo3::prelude::*;
@@ -21,6 +21,14 @@ pub trait Validator: std::fmt::Debug {
     fn validate(&mut self, value: &str) -> bool;
 }
 
+/// Combines validators that ran over separate parts of the same input, such as
+/// chunks of a file inferred in parallel.
+pub trait Merge {
+    /// Merges `other` into `self`, as though `self` had also validated every
+    /// value `other` did. Returns whether the merged validator is still valid.
+    fn merge(&mut self, other: Self) -> bool;
+}
+
 #[cfg(feature = "python")]
 impl Validator for PyObject {
     fn validate(&mut self, value: &str) -> bool {
@@ -28,7 +36,14 @@ impl Validator for PyObject {
     }
 }
 
-#[derive(Debug, Clone, Validator)]
+#[cfg(feature = "python")]
+impl Merge for PyObject {
+    fn merge(&mut self, _other: Self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Validator, Merge)]
 pub enum DataType {
     Text(Text),
     Integer(Integer),
@@ -45,3 +60,25 @@ pub enum DataType {
     #[cfg(feature = "python")]
     Py(PyObject),
 }
+
+impl DataType {
+    /// The name of the variant, as displayed in tables.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataType::Text(_) => "Text",
+            DataType::Integer(_) => "Integer",
+            DataType::Float(_) => "Float",
+            DataType::Empty(_) => "Empty",
+            DataType::Literal(_) => "Literal",
+            DataType::Unique(_) => "Unique",
+            #[cfg(feature = "time")]
+            DataType::Date(_) => "Date",
+            #[cfg(feature = "time")]
+            DataType::Time(_) => "Time",
+            #[cfg(feature = "time")]
+            DataType::DateTime(_) => "DateTime",
+            #[cfg(feature = "python")]
+            DataType::Py(_) => "PyObject",
+        }
+    }
+}
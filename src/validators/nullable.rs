@@ -1,4 +1,4 @@
-use crate::Validator;
+use crate::{validators::Merge, Validator};
 
 #[derive(Debug, Clone)]
 pub struct Empty;
@@ -8,3 +8,9 @@ impl Validator for Empty {
         value.is_empty()
     }
 }
+
+impl Merge for Empty {
+    fn merge(&mut self, _other: Self) -> bool {
+        true
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Validator;
+use crate::{validators::Merge, Validator};
 
 #[derive(Default, Debug, Clone)]
 pub struct Integer {
@@ -23,6 +23,15 @@ impl Validator for Integer {
     }
 }
 
+impl Merge for Integer {
+    fn merge(&mut self, other: Self) -> bool {
+        self.min_value = merge_with(self.min_value, other.min_value, std::cmp::min);
+        self.max_value = merge_with(self.max_value, other.max_value, std::cmp::max);
+        self.leading_plus |= other.leading_plus;
+        true
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct Float {
     pub min_value: Option<f64>,
@@ -48,9 +57,26 @@ impl Validator for Float {
     }
 }
 
+impl Merge for Float {
+    fn merge(&mut self, other: Self) -> bool {
+        self.min_value = merge_with(self.min_value, other.min_value, f64::min);
+        self.max_value = merge_with(self.max_value, other.max_value, f64::max);
+        self.leading_plus |= other.leading_plus;
+        self.e_notation |= other.e_notation;
+        true
+    }
+}
+
+pub(crate) fn merge_with<T>(a: Option<T>, b: Option<T>, f: impl Fn(T, T) -> T) -> Option<T> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(f(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{Float, Integer, Validator};
+    use crate::{validators::Merge, Float, Integer, Validator};
 
     #[test]
     fn integer() {
@@ -75,4 +101,17 @@ mod test {
         assert_eq!(validator.min_value, Some(-1.0));
         assert!(!validator.validate("Ferris"));
     }
+
+    #[test]
+    fn merge() {
+        let mut validator = Integer::default();
+        assert!(validator.validate("2"));
+        let mut other = Integer::default();
+        assert!(other.validate("-1"));
+        assert!(other.validate("+1"));
+        assert!(validator.merge(other));
+        assert_eq!(validator.min_value, Some(-1));
+        assert_eq!(validator.max_value, Some(2));
+        assert!(validator.leading_plus);
+    }
 }
@@ -1,4 +1,4 @@
-use crate::Validator;
+use crate::{validators::numeric::merge_with, validators::Merge, Validator};
 
 #[derive(Default, Debug, Clone)]
 pub struct Text {
@@ -18,6 +18,14 @@ impl Validator for Text {
     }
 }
 
+impl Merge for Text {
+    fn merge(&mut self, other: Self) -> bool {
+        self.min_length = merge_with(self.min_length, other.min_length, std::cmp::min);
+        self.max_length = merge_with(self.max_length, other.max_length, std::cmp::max);
+        true
+    }
+}
+
 /// Validates on literal values provided at creation.
 /// For example, you could match on the values "True" and "False" to implement
 /// a boolean type.
@@ -39,6 +47,12 @@ impl Validator for Literal {
     }
 }
 
+impl Merge for Literal {
+    fn merge(&mut self, _other: Self) -> bool {
+        true
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{Literal, Text, Validator};
@@ -1,4 +1,4 @@
-use crate::Validator;
+use crate::{validators::Merge, Validator};
 use chrono::prelude::DateTime as ChronoDateTime;
 use chrono::prelude::{NaiveDate, NaiveDateTime, NaiveTime};
 
@@ -23,10 +23,32 @@ impl Default for Date {
     }
 }
 
+/// Narrows `formats` down to those that `parses`, unless none do, in which case
+/// they are left alone so that a rejected value can be tolerated. Each format
+/// is tried once.
+fn narrow<T>(formats: &mut Vec<T>, parses: impl Fn(&T) -> bool) -> bool {
+    let Some(first) = formats.iter().position(&parses) else {
+        return false;
+    };
+    let mut i = 0;
+    formats.retain(|format| {
+        i += 1;
+        i - 1 == first || (i - 1 > first && parses(format))
+    });
+    true
+}
+
 impl Validator for Date {
     fn validate(&mut self, value: &str) -> bool {
-        self.formats
-            .retain(|format| NaiveDate::parse_from_str(value, format).is_ok());
+        narrow(&mut self.formats, |format| {
+            NaiveDate::parse_from_str(value, format).is_ok()
+        })
+    }
+}
+
+impl Merge for Date {
+    fn merge(&mut self, other: Self) -> bool {
+        self.formats.retain(|format| other.formats.contains(format));
         !self.formats.is_empty()
     }
 }
@@ -52,8 +74,15 @@ impl Default for Time {
 
 impl Validator for Time {
     fn validate(&mut self, value: &str) -> bool {
-        self.formats
-            .retain(|format| NaiveTime::parse_from_str(value, format).is_ok());
+        narrow(&mut self.formats, |format| {
+            NaiveTime::parse_from_str(value, format).is_ok()
+        })
+    }
+}
+
+impl Merge for Time {
+    fn merge(&mut self, other: Self) -> bool {
+        self.formats.retain(|format| other.formats.contains(format));
         !self.formats.is_empty()
     }
 }
@@ -89,7 +118,7 @@ impl Default for DateTime {
 
 impl Validator for DateTime {
     fn validate(&mut self, value: &str) -> bool {
-        self.formats.retain(|format| match format {
+        narrow(&mut self.formats, |format| match format {
             DateTimeFormat::RFC2822 => ChronoDateTime::parse_from_rfc2822(value).is_ok(),
             DateTimeFormat::RFC3339 => ChronoDateTime::parse_from_rfc3339(value).is_ok(),
             DateTimeFormat::Strftime(strftime) => {
@@ -97,11 +126,17 @@ impl Validator for DateTime {
             }
             DateTimeFormat::Unix => value
                 .parse::<i64>()
-                .map(|timestamp| NaiveDateTime::from_timestamp_opt(timestamp, 0))
+                .map(|timestamp| ChronoDateTime::from_timestamp(timestamp, 0))
                 .ok()
                 .flatten()
                 .is_some(),
-        });
+        })
+    }
+}
+
+impl Merge for DateTime {
+    fn merge(&mut self, other: Self) -> bool {
+        self.formats.retain(|format| other.formats.contains(format));
         !self.formats.is_empty()
     }
 }
@@ -109,6 +144,7 @@ impl Validator for DateTime {
 #[cfg(test)]
 mod test {
     use crate::validators::time::DateTimeFormat;
+    use crate::validators::Merge;
     use crate::Validator;
     use crate::{Date, DateTime, Time};
 
@@ -119,6 +155,7 @@ mod test {
         assert_eq!(1, validator.formats.len());
         assert_eq!("%Y-%m-%d", validator.formats[0]);
         assert!(!validator.validate("22/01/2001"));
+        assert_eq!(1, validator.formats.len());
 
         let mut validator = Date {
             formats: vec!["%Y %m %d".into()],
@@ -163,4 +200,18 @@ mod test {
         assert!(validator.validate("980121600"));
         assert!(!validator.validate("2001-01-22T00:00:00+00:00"));
     }
+
+    #[test]
+    fn merge() {
+        let mut validator = Date::default();
+        assert!(validator.validate("01/02/2001"));
+        let mut other = Date::default();
+        assert!(other.validate("22/01/2001"));
+        assert!(validator.merge(other));
+        assert_eq!(vec!["%d/%m/%Y"], validator.formats);
+
+        let mut other = Date::default();
+        assert!(other.validate("01/22/2001"));
+        assert!(!validator.merge(other));
+    }
 }
@@ -1,21 +1,329 @@
-use std::collections::HashSet;
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+};
 
-use crate::Validator;
+use crate::{validators::Merge, Validator};
 
-#[derive(Default, Debug, Clone)]
+/// Memory taken by the Bloom filter of a [`Unique`] with a limit, in bytes,
+/// unless set.
+pub const DEFAULT_BLOOM_BYTES: usize = 16 << 20;
+
+/// Validates that no value is seen twice.
+///
+/// By default every distinct value is kept, which takes memory in proportion to
+/// the input. With a [limit](Unique::bounded), values are kept only until there
+/// are more than that many, after which they are tracked by a Bloom filter of a
+/// fixed size instead, along with a HyperLogLog sketch estimating the number of
+/// distinct values. Every value seen again is then rejected, but so are a few
+/// new values the filter takes for ones already seen, more of them the fuller
+/// it gets: around 1% once it holds a value for every 10 bits, and 0.1% at 16
+/// bits. To infer a column as unique regardless, raise the
+/// [`Tolerance`](crate::Tolerance) for `Unique` to a [ratio](crate::Tolerance::Ratio)
+/// of about the [false positive rate](Unique::false_positive_rate).
+#[derive(Debug, Clone)]
 pub struct Unique {
+    /// How many distinct values are kept before falling back to the Bloom
+    /// filter. Unbounded when `None`.
+    pub limit: Option<usize>,
+    /// Size of the Bloom filter, in bytes.
+    pub bloom_bytes: usize,
     values: HashSet<String>,
+    sketch: Option<Sketch>,
+}
+
+impl Default for Unique {
+    fn default() -> Self {
+        Unique {
+            limit: None,
+            bloom_bytes: DEFAULT_BLOOM_BYTES,
+            values: HashSet::new(),
+            sketch: None,
+        }
+    }
+}
+
+impl Unique {
+    /// Keeps up to `limit` distinct values, and then a Bloom filter of
+    /// `bloom_bytes`, which suits about `bloom_bytes * 8 / 10` values.
+    pub fn bounded(limit: usize, bloom_bytes: usize) -> Self {
+        Unique {
+            limit: Some(limit),
+            bloom_bytes,
+            ..Unique::default()
+        }
+    }
+
+    /// Whether every value has been kept, so that the answer is certain rather
+    /// than probable.
+    pub fn is_exact(&self) -> bool {
+        self.sketch.is_none()
+    }
+
+    /// The number of distinct values seen, estimated once no longer
+    /// [exact](Unique::is_exact).
+    pub fn distinct(&self) -> u64 {
+        match self.sketch {
+            Some(ref sketch) => sketch.hyper_log_log.estimate(),
+            None => self.values.len() as u64,
+        }
+    }
+
+    /// The share of new values the Bloom filter, as full as it is now, takes
+    /// for ones already seen. Zero while [exact](Unique::is_exact).
+    pub fn false_positive_rate(&self) -> f64 {
+        self.sketch
+            .as_ref()
+            .map_or(0.0, |sketch| sketch.bloom.false_positive_rate())
+    }
+
+    /// Moves the kept values into a sketch, if past the limit.
+    fn spill(&mut self) {
+        if self.limit.is_some_and(|limit| self.values.len() > limit) {
+            let sketch = self
+                .sketch
+                .get_or_insert_with(|| Sketch::new(self.bloom_bytes * 8));
+            for value in std::mem::take(&mut self.values) {
+                sketch.insert(hash(&value));
+            }
+        }
+    }
 }
 
 impl Validator for Unique {
     fn validate(&mut self, value: &str) -> bool {
-        self.values.insert(value.to_string())
+        match self.sketch {
+            Some(ref mut sketch) => {
+                let hash = hash(value);
+                let seen = sketch.bloom.contains(hash);
+                sketch.insert(hash);
+                !seen
+            }
+            None => {
+                let unique = self.values.insert(value.to_string());
+                self.spill();
+                unique
+            }
+        }
+    }
+}
+
+impl Merge for Unique {
+    fn merge(&mut self, mut other: Self) -> bool {
+        match (self.sketch.take(), other.sketch.take()) {
+            (None, None) => {
+                let unique = self.values.is_disjoint(&other.values);
+                self.values.extend(other.values);
+                self.spill();
+                unique
+            }
+            (Some(mut sketch), None) | (None, Some(mut sketch)) => {
+                let values = std::mem::take(&mut self.values)
+                    .into_iter()
+                    .chain(other.values);
+                let mut seen = false;
+                for value in values {
+                    let hash = hash(&value);
+                    seen |= sketch.bloom.contains(hash);
+                    sketch.insert(hash);
+                }
+                let unique = !seen;
+                self.sketch = Some(sketch);
+                unique
+            }
+            (Some(mut sketch), Some(other)) => {
+                let unique = sketch.merge(other);
+                self.sketch = Some(sketch);
+                unique
+            }
+        }
+    }
+}
+
+fn hash(value: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[derive(Clone)]
+struct Sketch {
+    bloom: Bloom,
+    hyper_log_log: HyperLogLog,
+    /// Number of values inserted, all of them distinct as far as is known.
+    values: u64,
+}
+
+impl fmt::Debug for Sketch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sketch")
+            .field("bloom_bits", &(self.bloom.bits.len() * 64))
+            .field("values", &self.values)
+            .field("estimate", &self.hyper_log_log.estimate())
+            .finish()
+    }
+}
+
+impl Sketch {
+    fn new(bloom_bits: usize) -> Self {
+        Sketch {
+            bloom: Bloom::new(bloom_bits),
+            hyper_log_log: HyperLogLog::default(),
+            values: 0,
+        }
+    }
+
+    fn insert(&mut self, hash: u64) {
+        self.bloom.insert(hash);
+        self.hyper_log_log.insert(hash);
+        self.values += 1;
+    }
+
+    /// Merges in a sketch of other values, returning whether the two are
+    /// likely disjoint, judging by whether the estimated number of distinct
+    /// values across both comes close to the total.
+    fn merge(&mut self, other: Sketch) -> bool {
+        let values = self.values + other.values;
+        let unique = self.bloom.merge(&other.bloom) && {
+            self.hyper_log_log.merge(&other.hyper_log_log);
+            self.hyper_log_log.estimate() as f64 >= values as f64 * (1.0 - 3.0 * HyperLogLog::ERROR)
+        };
+        self.values = values;
+        unique
+    }
+}
+
+/// Hashes per value, which suits around 10 bits per value.
+const BLOOM_HASHES: u64 = 7;
+
+#[derive(Clone)]
+struct Bloom {
+    bits: Vec<u64>,
+    /// Number of bits set.
+    set: u64,
+}
+
+impl Bloom {
+    fn new(bits: usize) -> Self {
+        Bloom {
+            bits: vec![0; bits.div_ceil(64).max(1)],
+            set: 0,
+        }
+    }
+
+    /// The bits for `hash`, by double hashing.
+    fn indices(&self, hash: u64) -> impl Iterator<Item = usize> + use<> {
+        let len = self.bits.len() as u64 * 64;
+        let (a, b) = (hash & 0xffff_ffff, (hash >> 32) | 1);
+        (0..BLOOM_HASHES).map(move |i| (a.wrapping_add(i.wrapping_mul(b)) % len) as usize)
+    }
+
+    fn insert(&mut self, hash: u64) {
+        for i in self.indices(hash) {
+            let bit = 1 << (i % 64);
+            if self.bits[i / 64] & bit == 0 {
+                self.bits[i / 64] |= bit;
+                self.set += 1;
+            }
+        }
+    }
+
+    fn contains(&self, hash: u64) -> bool {
+        self.indices(hash)
+            .all(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    /// The chance that a new value finds all its bits set.
+    fn false_positive_rate(&self) -> f64 {
+        (self.set as f64 / (self.bits.len() * 64) as f64).powi(BLOOM_HASHES as i32)
+    }
+
+    /// Ors in another filter of the same size, returning false if the sizes
+    /// differ.
+    fn merge(&mut self, other: &Bloom) -> bool {
+        if self.bits.len() != other.bits.len() {
+            return false;
+        }
+        for (bits, other) in self.bits.iter_mut().zip(&other.bits) {
+            *bits |= other;
+        }
+        self.set = self.bits.iter().map(|bits| bits.count_ones() as u64).sum();
+        true
+    }
+}
+
+/// Bits of the hash picking a register.
+const PRECISION: u32 = 14;
+
+#[derive(Clone)]
+struct HyperLogLog {
+    registers: Vec<u8>,
+    /// The sum of `2^-register` over the registers, and how many are zero,
+    /// kept up to date so that estimates are cheap.
+    sum: f64,
+    zeros: usize,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        HyperLogLog {
+            registers: vec![0; 1 << PRECISION],
+            sum: (1 << PRECISION) as f64,
+            zeros: 1 << PRECISION,
+        }
+    }
+}
+
+impl HyperLogLog {
+    /// Standard error of the estimate.
+    const ERROR: f64 = 1.04 / (1 << (PRECISION / 2)) as f64;
+
+    fn insert(&mut self, hash: u64) {
+        let register = (hash >> (64 - PRECISION)) as usize;
+        let rank = ((hash << PRECISION) | (1 << (PRECISION - 1))).leading_zeros() as u8 + 1;
+        let old = self.registers[register];
+        if rank > old {
+            self.registers[register] = rank;
+            self.sum += 2f64.powi(-(rank as i32)) - 2f64.powi(-(old as i32));
+            if old == 0 {
+                self.zeros -= 1;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &HyperLogLog) {
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+        self.sum = self
+            .registers
+            .iter()
+            .map(|&register| 2f64.powi(-(register as i32)))
+            .sum();
+        self.zeros = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let estimate = alpha * m * m / self.sum;
+        if estimate <= 2.5 * m && self.zeros > 0 {
+            // Linear counting is more accurate for small cardinalities.
+            (m * (m / self.zeros as f64).ln()).round() as u64
+        } else {
+            estimate.round() as u64
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{Unique, Validator};
+    use crate::{validators::Merge, DataType, Field, Tolerance, Unique, Validator};
 
     #[test]
     fn id() {
@@ -24,4 +332,64 @@ mod test {
         assert!(validator.validate("Corro"));
         assert!(!validator.validate("Ferris"));
     }
+
+    #[test]
+    fn merge() {
+        let mut validator = Unique::default();
+        assert!(validator.validate("Ferris"));
+        let mut other = Unique::default();
+        assert!(other.validate("Corro"));
+        assert!(validator.clone().merge(other.clone()));
+        assert!(other.validate("Ferris"));
+        assert!(!validator.merge(other));
+    }
+
+    #[test]
+    fn bounded() {
+        let mut validator = Unique::bounded(100, 1 << 17);
+        for i in 0..10000 {
+            assert!(validator.validate(&i.to_string()));
+        }
+        assert!(!validator.is_exact());
+        assert!(!validator.validate("42"));
+        let distinct = validator.distinct();
+        assert!((9800..10200).contains(&distinct), "{distinct}");
+
+        let mut other = Unique::bounded(100, 1 << 17);
+        for i in 10000..10050 {
+            assert!(other.validate(&i.to_string()));
+        }
+        assert!(other.is_exact());
+        assert!(validator.clone().merge(other.clone()));
+        assert!(other.validate("7"));
+        assert!(!validator.merge(other));
+    }
+
+    #[test]
+    fn scale() {
+        // 16 bits per value.
+        let mut validator = Unique::bounded(10_000, 2 << 20);
+        let rejected = (0..1_000_000)
+            .filter(|i| !validator.validate(&i.to_string()))
+            .count();
+        assert!(rejected < 1000, "{rejected}");
+        assert!(validator.false_positive_rate() < 0.001);
+        let distinct = validator.distinct();
+        assert!((970_000..1_030_000).contains(&distinct), "{distinct}");
+
+        // Every value seen again is caught, long after the switch to the
+        // sketch.
+        for i in (100_000..1_000_000).step_by(20_000) {
+            assert!(!validator.validate(&i.to_string()), "{i}");
+        }
+
+        let mut field = Field::new(
+            None,
+            vec![DataType::Unique(Unique::bounded(10_000, 2 << 20))],
+        )
+        .with_tolerance(|_| Tolerance::Ratio(0.001));
+        (0..1_000_000).for_each(|i| field.consider(&i.to_string()));
+        field.settle();
+        assert!(matches!(field.data_type(), Some(DataType::Unique(_))));
+    }
 }